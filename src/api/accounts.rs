@@ -7,12 +7,13 @@ use axum::{
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::state::AppState;
-use crate::db::{self, Account};
+use crate::db::{Account, StoreError};
 
 /// Account response (without sensitive tokens)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AccountResponse {
     pub id: i64,
     pub email: String,
@@ -38,7 +39,7 @@ impl From<Account> for AccountResponse {
 }
 
 /// Toggle account request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ToggleAccountRequest {
     pub is_active: bool,
 }
@@ -52,92 +53,159 @@ pub fn account_routes() -> Router<AppState> {
         .route("/{id}", delete(delete_account))
         .route("/{id}/toggle", put(toggle_account))
         .route("/{id}/current", post(set_current))
+        .route("/{id}/refresh", post(force_refresh))
 }
 
 /// List all accounts
-async fn list_accounts(
+#[utoipa::path(
+    get,
+    path = "/api/accounts",
+    responses((status = 200, description = "All accounts", body = [AccountResponse]))
+)]
+pub(crate) async fn list_accounts(
     State(state): State<AppState>,
-) -> Result<Json<Vec<AccountResponse>>, StatusCode> {
-    let accounts = state
-        .db
-        .with_conn(|conn| db::get_all_accounts(conn))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
+) -> Result<Json<Vec<AccountResponse>>, StoreError> {
+    let accounts = state.db.get_all_accounts().await?;
     Ok(Json(accounts.into_iter().map(Into::into).collect()))
 }
 
 /// Get account by ID
-async fn get_account(
+#[utoipa::path(
+    get,
+    path = "/api/accounts/{id}",
+    params(("id" = i64, Path, description = "Account ID")),
+    responses(
+        (status = 200, description = "The account", body = AccountResponse),
+        (status = 404, description = "No account with that ID"),
+    )
+)]
+pub(crate) async fn get_account(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<Json<AccountResponse>, StatusCode> {
+) -> Result<Json<AccountResponse>, StoreError> {
     let account = state
         .db
-        .with_conn(|conn| db::get_account_by_id(conn, id))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .get_account_by_id(id)
+        .await?
+        .ok_or(StoreError::NotFound)?;
 
     Ok(Json(account.into()))
 }
 
 /// Get current account
-async fn get_current(
+#[utoipa::path(
+    get,
+    path = "/api/accounts/current",
+    responses((status = 200, description = "The selected account, if any", body = Option<AccountResponse>))
+)]
+pub(crate) async fn get_current(
     State(state): State<AppState>,
-) -> Result<Json<Option<AccountResponse>>, StatusCode> {
-    let account = state
-        .db
-        .with_conn(|conn| db::get_current_account(conn))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
+) -> Result<Json<Option<AccountResponse>>, StoreError> {
+    let account = state.db.get_current_account().await?;
     Ok(Json(account.map(Into::into)))
 }
 
 /// Delete account
-async fn delete_account(
+#[utoipa::path(
+    delete,
+    path = "/api/accounts/{id}",
+    params(("id" = i64, Path, description = "Account ID")),
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 404, description = "No account with that ID"),
+    )
+)]
+pub(crate) async fn delete_account(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<StatusCode, StatusCode> {
-    let deleted = state
-        .db
-        .with_conn(|conn| db::delete_account(conn, id))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<StatusCode, StoreError> {
+    let deleted = state.db.delete_account(id).await?;
 
     if deleted {
         Ok(StatusCode::NO_CONTENT)
     } else {
-        Err(StatusCode::NOT_FOUND)
+        Err(StoreError::NotFound)
     }
 }
 
 /// Toggle account active status
-async fn toggle_account(
+#[utoipa::path(
+    put,
+    path = "/api/accounts/{id}/toggle",
+    params(("id" = i64, Path, description = "Account ID")),
+    responses(
+        (status = 200, description = "New active state", body = serde_json::Value),
+        (status = 404, description = "No account with that ID"),
+    )
+)]
+pub(crate) async fn toggle_account(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let is_active = state
+) -> Result<Json<serde_json::Value>, StoreError> {
+    // Verify account exists
+    state
         .db
-        .with_conn(|conn| db::toggle_account_active(conn, id))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .get_account_by_id(id)
+        .await?
+        .ok_or(StoreError::NotFound)?;
 
+    let is_active = state.db.toggle_account_active(id).await?;
     Ok(Json(serde_json::json!({ "is_active": is_active })))
 }
 
 /// Set current account
-async fn set_current(
+#[utoipa::path(
+    post,
+    path = "/api/accounts/{id}/current",
+    params(("id" = i64, Path, description = "Account ID")),
+    responses(
+        (status = 200, description = "Account selected"),
+        (status = 404, description = "No account with that ID"),
+    )
+)]
+pub(crate) async fn set_current(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, StoreError> {
     // Verify account exists
-    let _ = state
+    state
         .db
-        .with_conn(|conn| db::get_account_by_id(conn, id))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .get_account_by_id(id)
+        .await?
+        .ok_or(StoreError::NotFound)?;
+
+    state.db.set_current_account(Some(id)).await?;
+
+    Ok(StatusCode::OK)
+}
 
+/// Force an immediate OAuth token refresh for an account
+#[utoipa::path(
+    post,
+    path = "/api/accounts/{id}/refresh",
+    params(("id" = i64, Path, description = "Account ID")),
+    responses(
+        (status = 200, description = "Refresh succeeded"),
+        (status = 404, description = "No account with that ID"),
+        (status = 502, description = "Refresh request to Google failed"),
+    )
+)]
+pub(crate) async fn force_refresh(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
     state
         .db
-        .with_conn(|conn| db::set_current_account(conn, Some(id)))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .get_account_by_id(id)
+        .await
+        .map_err(|e| e.status_code())?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    state
+        .refresher
+        .refresh_account(id)
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?;
 
     Ok(StatusCode::OK)
 }
@@ -0,0 +1,98 @@
+//! API-key authentication middleware for `/api` routes
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::state::AppState;
+
+/// Label of the API key that authenticated the current request, stashed in
+/// request extensions so `MonitorLog` rows can record which key was used.
+#[derive(Debug, Clone)]
+pub struct ApiKeyLabel(pub String);
+
+/// Validate the caller's `x-api-key` header against the legacy shared
+/// `ProxyConfig.api_key` and the rotating `api_keys` table, rejecting keys
+/// that are revoked or outside their validity window. Requests pass through
+/// unauthenticated when no key is configured anywhere, so existing
+/// deployments that haven't set one up keep working.
+pub async fn api_key_auth(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let config = state
+        .db
+        .get_proxy_config()
+        .await
+        .map_err(|e| e.status_code())?;
+    let keys = state
+        .db
+        .list_api_keys()
+        .await
+        .map_err(|e| e.status_code())?;
+
+    if config.api_key.is_none() && keys.is_empty() {
+        return Ok(next.run(request).await);
+    }
+
+    let presented = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if let Some(expected) = &config.api_key {
+        if constant_time_eq(presented.as_bytes(), expected.as_bytes()) {
+            request
+                .extensions_mut()
+                .insert(ApiKeyLabel("proxy config key".to_string()));
+            return Ok(next.run(request).await);
+        }
+    }
+
+    let presented_hash = hash_key(presented);
+    let now = now();
+
+    let matched = keys.into_iter().find(|key| {
+        !key.revoked
+            && key.valid_from.map_or(true, |t| now >= t)
+            && key.valid_until.map_or(true, |t| now <= t)
+            && constant_time_eq(key.key_hash.as_bytes(), presented_hash.as_bytes())
+    });
+
+    match matched {
+        Some(key) => {
+            request.extensions_mut().insert(ApiKeyLabel(key.label));
+            Ok(next.run(request).await)
+        }
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// SHA-256 hex digest of a raw API key, matching how keys are stored
+pub fn hash_key(raw: &str) -> String {
+    let digest = Sha256::digest(raw.as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Compare two byte strings in time independent of where they first differ,
+/// so a timing attack can't be used to guess a valid key
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
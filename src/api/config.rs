@@ -1,43 +1,161 @@
 //! Configuration API endpoints
 
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
-    routing::{get, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use super::auth::hash_key;
 use super::state::AppState;
-use crate::db::{self, ProxyConfig};
+use crate::db::{ApiKey, ProxyConfig, StoreError};
 
 /// Build config routes
 pub fn config_routes() -> Router<AppState> {
     Router::new()
         .route("/proxy", get(get_proxy_config))
         .route("/proxy", put(update_proxy_config))
+        .route("/api-keys", get(list_api_keys))
+        .route("/api-keys", post(create_api_key))
+        .route("/api-keys/{id}", delete(revoke_api_key))
 }
 
 /// Get proxy configuration
-async fn get_proxy_config(
+#[utoipa::path(
+    get,
+    path = "/api/config/proxy",
+    responses((status = 200, description = "Current proxy configuration", body = ProxyConfig))
+)]
+pub(crate) async fn get_proxy_config(
     State(state): State<AppState>,
-) -> Result<Json<ProxyConfig>, StatusCode> {
-    let config = state
-        .db
-        .with_conn(|conn| db::get_proxy_config(conn))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
+) -> Result<Json<ProxyConfig>, StoreError> {
+    let config = state.db.get_proxy_config().await?;
     Ok(Json(config))
 }
 
 /// Update proxy configuration
-async fn update_proxy_config(
+#[utoipa::path(
+    put,
+    path = "/api/config/proxy",
+    request_body = ProxyConfig,
+    responses((status = 200, description = "Configuration saved"))
+)]
+pub(crate) async fn update_proxy_config(
     State(state): State<AppState>,
     Json(config): Json<ProxyConfig>,
-) -> Result<StatusCode, StatusCode> {
-    state
+) -> Result<StatusCode, StoreError> {
+    state.db.save_proxy_config(&config).await?;
+    Ok(StatusCode::OK)
+}
+
+/// API key metadata returned to the dashboard; the raw key and its hash are
+/// never included
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeySummary {
+    pub id: i64,
+    pub label: String,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+    pub revoked: bool,
+    pub created_at: i64,
+}
+
+impl From<ApiKey> for ApiKeySummary {
+    fn from(k: ApiKey) -> Self {
+        Self {
+            id: k.id,
+            label: k.label,
+            valid_from: k.valid_from,
+            valid_until: k.valid_until,
+            revoked: k.revoked,
+            created_at: k.created_at,
+        }
+    }
+}
+
+/// Request body to mint a new API key
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiKeyRequest {
+    pub label: String,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+}
+
+/// Response containing the raw key; shown only once, at creation time
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateApiKeyResponse {
+    pub id: i64,
+    pub key: String,
+    pub label: String,
+}
+
+/// List API keys
+#[utoipa::path(
+    get,
+    path = "/api/config/api-keys",
+    responses((status = 200, description = "All API keys", body = [ApiKeySummary]))
+)]
+pub(crate) async fn list_api_keys(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ApiKeySummary>>, StoreError> {
+    let keys = state.db.list_api_keys().await?;
+    Ok(Json(keys.into_iter().map(Into::into).collect()))
+}
+
+/// Mint a new API key; the raw key is returned once here and never stored
+#[utoipa::path(
+    post,
+    path = "/api/config/api-keys",
+    request_body = CreateApiKeyRequest,
+    responses((status = 200, description = "Key minted", body = CreateApiKeyResponse))
+)]
+pub(crate) async fn create_api_key(
+    State(state): State<AppState>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreateApiKeyResponse>, StoreError> {
+    let raw_key = generate_key()?;
+    let key_hash = hash_key(&raw_key);
+
+    let id = state
         .db
-        .with_conn(|conn| db::save_proxy_config(conn, &config))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .create_api_key(&key_hash, &req.label, req.valid_from, req.valid_until)
+        .await?;
 
-    Ok(StatusCode::OK)
+    Ok(Json(CreateApiKeyResponse {
+        id,
+        key: raw_key,
+        label: req.label,
+    }))
+}
+
+/// Revoke an API key so it's rejected regardless of its validity window
+#[utoipa::path(
+    delete,
+    path = "/api/config/api-keys/{id}",
+    params(("id" = i64, Path, description = "API key ID")),
+    responses(
+        (status = 200, description = "Key revoked"),
+        (status = 404, description = "No API key with that ID"),
+    )
+)]
+pub(crate) async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StoreError> {
+    if state.db.revoke_api_key(id).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(StoreError::NotFound)
+    }
+}
+
+/// Generate a random API key, URL-safe base64 encoded
+fn generate_key() -> Result<String, StoreError> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| StoreError::Backend(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
 }
@@ -1,10 +1,15 @@
 //! REST API module for dashboard endpoints
 
 mod accounts;
+mod auth;
 mod config;
 mod monitor;
+mod openapi;
+mod rate_limit;
 mod routes;
 mod state;
 
+pub use auth::ApiKeyLabel;
+pub use openapi::ApiDoc;
 pub use routes::api_router;
 pub use state::AppState;
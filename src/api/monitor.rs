@@ -7,12 +7,13 @@ use axum::{
     Json, Router,
 };
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use super::state::AppState;
-use crate::db::{self, LogStats, MonitorLog};
+use crate::db::{LogStats, MonitorLog, StoreError, TimeseriesBucket};
 
 /// Pagination query params
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PaginationParams {
     #[serde(default = "default_limit")]
     pub limit: u32,
@@ -25,7 +26,7 @@ fn default_limit() -> u32 {
 }
 
 /// Logs response with pagination info
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct LogsResponse {
     pub logs: Vec<MonitorLog>,
     pub total: u64,
@@ -33,28 +34,41 @@ pub struct LogsResponse {
     pub offset: u32,
 }
 
+/// Query params for the time-bucketed analytics endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TimeseriesParams {
+    /// Unix timestamp (seconds), inclusive
+    pub from: i64,
+    /// Unix timestamp (seconds), inclusive
+    pub to: i64,
+    /// Bucket width: `hour` or `day`
+    pub bucket: String,
+    pub account_email: Option<String>,
+    pub model: Option<String>,
+}
+
 /// Build monitor routes
 pub fn monitor_routes() -> Router<AppState> {
     Router::new()
         .route("/logs", get(get_logs))
         .route("/logs", delete(clear_logs))
         .route("/stats", get(get_stats))
+        .route("/stats/timeseries", get(get_timeseries))
 }
 
 /// Get logs with pagination
-async fn get_logs(
+#[utoipa::path(
+    get,
+    path = "/api/monitor/logs",
+    params(PaginationParams),
+    responses((status = 200, description = "Paginated logs", body = LogsResponse))
+)]
+pub(crate) async fn get_logs(
     State(state): State<AppState>,
     Query(params): Query<PaginationParams>,
-) -> Result<Json<LogsResponse>, StatusCode> {
-    let logs = state
-        .db
-        .with_conn(|conn| db::get_logs(conn, params.limit, params.offset))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let total = state
-        .db
-        .with_conn(|conn| db::get_log_count(conn))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<LogsResponse>, StoreError> {
+    let logs = state.db.get_logs(params.limit, params.offset).await?;
+    let total = state.db.get_log_count().await?;
 
     Ok(Json(LogsResponse {
         logs,
@@ -65,21 +79,59 @@ async fn get_logs(
 }
 
 /// Clear all logs
-async fn clear_logs(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
-    let count = state
-        .db
-        .with_conn(|conn| db::clear_logs(conn))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
+#[utoipa::path(
+    delete,
+    path = "/api/monitor/logs",
+    responses((status = 200, description = "Number of logs deleted", body = serde_json::Value))
+)]
+pub(crate) async fn clear_logs(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StoreError> {
+    let count = state.db.clear_logs().await?;
     Ok(Json(serde_json::json!({ "deleted": count })))
 }
 
 /// Get statistics
-async fn get_stats(State(state): State<AppState>) -> Result<Json<LogStats>, StatusCode> {
-    let stats = state
+#[utoipa::path(
+    get,
+    path = "/api/monitor/stats",
+    responses((status = 200, description = "All-time log statistics", body = LogStats))
+)]
+pub(crate) async fn get_stats(State(state): State<AppState>) -> Result<Json<LogStats>, StoreError> {
+    let stats = state.db.get_stats().await?;
+    Ok(Json(stats))
+}
+
+/// Usage over time, bucketed by `hour` or `day` and optionally filtered by
+/// account/model, for dashboard charts
+#[utoipa::path(
+    get,
+    path = "/api/monitor/stats/timeseries",
+    params(TimeseriesParams),
+    responses(
+        (status = 200, description = "Time-bucketed stats", body = [TimeseriesBucket]),
+        (status = 400, description = "Unknown bucket width"),
+    )
+)]
+pub(crate) async fn get_timeseries(
+    State(state): State<AppState>,
+    Query(params): Query<TimeseriesParams>,
+) -> Result<Json<Vec<TimeseriesBucket>>, StatusCode> {
+    let bucket_seconds = match params.bucket.as_str() {
+        "hour" => 3600,
+        "day" => 86400,
+        _ => return Err(StatusCode::BAD_REQUEST),
+    };
+
+    let buckets = state
         .db
-        .with_conn(|conn| db::get_stats(conn))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .get_timeseries(
+            params.from,
+            params.to,
+            bucket_seconds,
+            params.account_email.as_deref(),
+            params.model.as_deref(),
+        )
+        .await
+        .map_err(|e| e.status_code())?;
 
-    Ok(Json(stats))
+    Ok(Json(buckets))
 }
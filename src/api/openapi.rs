@@ -0,0 +1,41 @@
+//! Aggregated OpenAPI document for the dashboard API
+
+use utoipa::OpenApi;
+
+use super::{accounts, config, monitor};
+use crate::db::{MonitorLog, ProxyConfig, TimeseriesBucket};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        accounts::list_accounts,
+        accounts::get_account,
+        accounts::get_current,
+        accounts::delete_account,
+        accounts::toggle_account,
+        accounts::set_current,
+        accounts::force_refresh,
+        config::get_proxy_config,
+        config::update_proxy_config,
+        config::list_api_keys,
+        config::create_api_key,
+        config::revoke_api_key,
+        monitor::get_logs,
+        monitor::clear_logs,
+        monitor::get_stats,
+        monitor::get_timeseries,
+    ),
+    components(schemas(
+        accounts::AccountResponse,
+        accounts::ToggleAccountRequest,
+        ProxyConfig,
+        config::ApiKeySummary,
+        config::CreateApiKeyRequest,
+        config::CreateApiKeyResponse,
+        monitor::LogsResponse,
+        MonitorLog,
+        crate::db::LogStats,
+        TimeseriesBucket,
+    ))
+)]
+pub struct ApiDoc;
@@ -0,0 +1,55 @@
+//! Rate-limiting middleware for `/api` routes
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Duration;
+
+use super::state::AppState;
+
+const PERIOD: Duration = Duration::from_secs(60);
+
+/// Throttle requests using the caller's `x-api-key` header, falling back to
+/// the currently selected account's email. Requests are let through
+/// untouched when no rate limit is configured for the resolved key.
+pub async fn rate_limit(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (key, max_per_period) = match resolve_limit(&state, &headers).await {
+        Some(pair) => pair,
+        None => return next.run(request).await,
+    };
+
+    let decision = state.limiter.check(&key, max_per_period, PERIOD).await;
+
+    if decision.allowed {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, decision.retry_after_secs.to_string())],
+        )
+            .into_response()
+    }
+}
+
+/// Resolve the bucket key and its configured requests-per-minute limit, or
+/// `None` when rate limiting does not apply to this request
+async fn resolve_limit(state: &AppState, headers: &HeaderMap) -> Option<(String, u32)> {
+    let config = state.db.get_proxy_config().await.ok()?;
+
+    if let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+        let rpm = config.rate_limit_rpm?;
+        return Some((api_key.to_string(), rpm));
+    }
+
+    let account = state.db.get_current_account().await.ok()??;
+    let rpm = account.rate_limit_rpm.or(config.rate_limit_rpm)?;
+    Some((account.email, rpm))
+}
@@ -1,19 +1,27 @@
 //! API routes configuration
 
-use axum::{routing::get, Router};
+use axum::{middleware, routing::get, Router};
 
 use super::accounts::account_routes;
+use super::auth::api_key_auth;
 use super::config::config_routes;
 use super::monitor::monitor_routes;
+use super::rate_limit::rate_limit;
 use super::state::AppState;
 
 /// Build the API router
+///
+/// The OpenAPI document and Swagger UI are served outside this router (see
+/// `main.rs`), since they have to be reachable before a caller has an API
+/// key to attach.
 pub fn api_router() -> Router<AppState> {
     Router::new()
         .route("/health", get(health))
         .nest("/accounts", account_routes())
         .nest("/config", config_routes())
         .nest("/monitor", monitor_routes())
+        .layer(middleware::from_fn(rate_limit))
+        .layer(middleware::from_fn(api_key_auth))
 }
 
 /// API health check
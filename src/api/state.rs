@@ -1,16 +1,32 @@
 //! Shared application state
 
-use crate::db::Database;
+use crate::auth::TokenRefresher;
+use crate::db::Store;
+use crate::metrics::Registry;
+use crate::ratelimit::RateLimiter;
 use std::sync::Arc;
 
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
-    pub db: Arc<Database>,
+    pub db: Arc<dyn Store>,
+    pub limiter: Arc<dyn RateLimiter>,
+    pub refresher: Arc<TokenRefresher>,
+    pub metrics: Arc<Registry>,
 }
 
 impl AppState {
-    pub fn new(db: Database) -> Self {
-        Self { db: Arc::new(db) }
+    pub fn new(
+        db: Arc<dyn Store>,
+        limiter: Arc<dyn RateLimiter>,
+        refresher: Arc<TokenRefresher>,
+        metrics: Arc<Registry>,
+    ) -> Self {
+        Self {
+            db,
+            limiter,
+            refresher,
+            metrics,
+        }
     }
 }
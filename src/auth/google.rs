@@ -2,6 +2,8 @@
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
 use url::Url;
 
 /// Google OAuth configuration
@@ -42,6 +44,14 @@ pub struct TokenResponse {
     pub scope: Option<String>,
 }
 
+/// CSRF state plus the PKCE verifier needed to complete the flow, returned
+/// together so the caller can stash both until the redirect comes back
+pub struct PendingAuth {
+    pub state: String,
+    pub code_verifier: String,
+    pub auth_url: String,
+}
+
 /// User info from Google
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserInfo {
@@ -50,6 +60,24 @@ pub struct UserInfo {
     pub picture: Option<String>,
 }
 
+/// Response from the device authorization endpoint, shown to the user as a
+/// short code they enter on a second device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCodeResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_url: String,
+    pub expires_in: u64,
+    pub interval: u64,
+}
+
+/// Error body returned by the token endpoint while a device-code flow is
+/// still pending or has been rejected
+#[derive(Debug, Default, Deserialize)]
+struct DeviceTokenError {
+    error: String,
+}
+
 impl GoogleOAuth {
     pub fn new(client_id: String, client_secret: String) -> Self {
         Self {
@@ -66,8 +94,35 @@ impl GoogleOAuth {
         URL_SAFE_NO_PAD.encode(bytes)
     }
 
+    /// Generate a PKCE code verifier: 32 random bytes, base64url-encoded (RFC 7636 `high-entropy cryptographic random STRING`)
+    pub fn generate_code_verifier() -> String {
+        let mut bytes = [0u8; 32];
+        getrandom::fill(&mut bytes).ok();
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Derive the `S256` PKCE code challenge for a verifier
+    pub fn code_challenge(code_verifier: &str) -> String {
+        let digest = Sha256::digest(code_verifier.as_bytes());
+        URL_SAFE_NO_PAD.encode(digest)
+    }
+
+    /// Start an authorization-code flow: generates CSRF state and a PKCE
+    /// verifier, and builds the URL the user should be sent to
+    pub fn begin_auth(&self) -> PendingAuth {
+        let state = Self::generate_state();
+        let code_verifier = Self::generate_code_verifier();
+        let auth_url = self.generate_auth_url(&state, &Self::code_challenge(&code_verifier));
+
+        PendingAuth {
+            state,
+            code_verifier,
+            auth_url,
+        }
+    }
+
     /// Generate the authorization URL
-    pub fn generate_auth_url(&self, state: &str) -> String {
+    pub fn generate_auth_url(&self, state: &str, code_challenge: &str) -> String {
         let mut url = Url::parse("https://accounts.google.com/o/oauth2/v2/auth").unwrap();
 
         url.query_pairs_mut()
@@ -77,13 +132,19 @@ impl GoogleOAuth {
             .append_pair("scope", &SCOPES.join(" "))
             .append_pair("access_type", "offline")
             .append_pair("prompt", "consent")
-            .append_pair("state", state);
+            .append_pair("state", state)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
 
         url.to_string()
     }
 
     /// Exchange authorization code for tokens
-    pub async fn exchange_code(&self, code: &str) -> Result<TokenResponse, AuthError> {
+    ///
+    /// `code_verifier` is the PKCE verifier returned by [`Self::begin_auth`]
+    /// for this flow; Google validates it against the `code_challenge` sent
+    /// to the authorization endpoint.
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<TokenResponse, AuthError> {
         let client = reqwest::Client::new();
 
         let response = client
@@ -94,6 +155,7 @@ impl GoogleOAuth {
                 ("code", code),
                 ("redirect_uri", self.redirect_uri.as_str()),
                 ("grant_type", "authorization_code"),
+                ("code_verifier", code_verifier),
             ])
             .send()
             .await
@@ -137,6 +199,75 @@ impl GoogleOAuth {
             .map_err(|e| AuthError::ParseFailed(e.to_string()))
     }
 
+    /// Request a device and user code for the Device Authorization Grant
+    /// (RFC 8628), for setups with no local browser to send through the
+    /// authorization-code redirect (e.g. headless servers on `allow_lan_access`)
+    pub async fn request_device_code(&self) -> Result<DeviceCodeResponse, AuthError> {
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post("https://oauth2.googleapis.com/device/code")
+            .form(&[("client_id", self.client_id.as_str()), ("scope", &SCOPES.join(" "))])
+            .send()
+            .await
+            .map_err(|e| AuthError::RequestFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AuthError::DeviceCodeFailed(error_text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AuthError::ParseFailed(e.to_string()))
+    }
+
+    /// Poll the token endpoint until the user approves (or rejects) the
+    /// device code, honoring `authorization_pending`/`slow_down` as defined
+    /// by RFC 8628. `interval` is the starting poll interval in seconds, as
+    /// returned by [`Self::request_device_code`].
+    pub async fn poll_device_token(&self, device_code: &str, interval: u64) -> Result<TokenResponse, AuthError> {
+        let client = reqwest::Client::new();
+        let mut interval = Duration::from_secs(interval.max(1));
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let response = client
+                .post("https://oauth2.googleapis.com/token")
+                .form(&[
+                    ("client_id", self.client_id.as_str()),
+                    ("client_secret", self.client_secret.as_str()),
+                    ("device_code", device_code),
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ])
+                .send()
+                .await
+                .map_err(|e| AuthError::RequestFailed(e.to_string()))?;
+
+            if response.status().is_success() {
+                return response
+                    .json()
+                    .await
+                    .map_err(|e| AuthError::ParseFailed(e.to_string()));
+            }
+
+            let error_text = response.text().await.unwrap_or_default();
+            let error = serde_json::from_str::<DeviceTokenError>(&error_text)
+                .map(|e| e.error)
+                .unwrap_or_default();
+
+            match error.as_str() {
+                "authorization_pending" => continue,
+                "slow_down" => interval += Duration::from_secs(5),
+                "access_denied" => return Err(AuthError::AccessDenied),
+                "expired_token" => return Err(AuthError::ExpiredToken),
+                _ => return Err(AuthError::TokenExchangeFailed(error_text)),
+            }
+        }
+    }
+
     /// Get user info using an access token
     pub async fn get_user_info(&self, access_token: &str) -> Result<UserInfo, AuthError> {
         let client = reqwest::Client::new();
@@ -183,4 +314,13 @@ pub enum AuthError {
 
     #[error("Missing authorization code")]
     MissingCode,
+
+    #[error("Failed to request device code: {0}")]
+    DeviceCodeFailed(String),
+
+    #[error("User denied the device authorization request")]
+    AccessDenied,
+
+    #[error("Device code expired before the user approved it")]
+    ExpiredToken,
 }
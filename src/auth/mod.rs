@@ -1,5 +1,7 @@
 //! Authentication module for OAuth flows
 
 mod google;
+mod refresher;
 
-pub use google::{AuthError, GoogleOAuth, TokenResponse, UserInfo, SCOPES};
+pub use google::{AuthError, DeviceCodeResponse, GoogleOAuth, PendingAuth, TokenResponse, UserInfo, SCOPES};
+pub use refresher::TokenRefresher;
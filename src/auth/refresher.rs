@@ -0,0 +1,166 @@
+//! Background OAuth token refresh for stored accounts
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::{error, info, warn};
+
+use super::google::GoogleOAuth;
+use crate::db::{MonitorLog, Store};
+
+/// Refresh accounts whose token expires within this many seconds
+const DEFAULT_SKEW_SECS: i64 = 300;
+/// How often to scan for accounts needing a refresh
+const DEFAULT_SCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Periodically refreshes OAuth tokens for stored accounts so the proxy
+/// never hits a 401 from an expired access token.
+///
+/// Refreshes are serialized per account so a slow upstream call can't cause
+/// the same refresh token to be spent twice.
+pub struct TokenRefresher {
+    store: Arc<dyn Store>,
+    oauth: GoogleOAuth,
+    skew_secs: i64,
+    scan_interval: Duration,
+    in_flight: Mutex<HashSet<i64>>,
+}
+
+impl TokenRefresher {
+    pub fn new(store: Arc<dyn Store>, oauth: GoogleOAuth) -> Self {
+        Self {
+            store,
+            oauth,
+            skew_secs: DEFAULT_SKEW_SECS,
+            scan_interval: DEFAULT_SCAN_INTERVAL,
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn with_skew_secs(mut self, skew_secs: i64) -> Self {
+        self.skew_secs = skew_secs;
+        self
+    }
+
+    /// Override how often the background task scans for accounts needing a refresh
+    ///
+    /// Clamped to at least one second: `tokio::time::interval` panics on a
+    /// zero duration, and an operator passing `0` almost certainly means
+    /// "as often as possible" rather than "never check again".
+    pub fn with_scan_interval(mut self, scan_interval: Duration) -> Self {
+        self.scan_interval = scan_interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Spawn the periodic scan loop as a background task
+    pub fn spawn(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.scan_interval);
+            loop {
+                interval.tick().await;
+                self.scan_and_refresh().await;
+            }
+        });
+    }
+
+    /// Scan all active accounts and refresh any whose token is near expiry
+    pub async fn scan_and_refresh(&self) {
+        let accounts = match self.store.get_active_accounts().await {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                error!("Failed to load accounts for token refresh: {err}");
+                return;
+            }
+        };
+
+        let deadline = now() + self.skew_secs;
+        for account in accounts {
+            if account.expires_at <= deadline {
+                if let Err(err) = self.refresh_account(account.id).await {
+                    warn!("Token refresh failed for account {}: {err}", account.id);
+                }
+            }
+        }
+    }
+
+    /// Force an immediate refresh for a single account, e.g. from the
+    /// dashboard's "refresh now" action
+    pub async fn refresh_account(&self, id: i64) -> Result<(), String> {
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            if !in_flight.insert(id) {
+                // Another refresh for this account is already in flight;
+                // avoid spending the refresh token twice.
+                return Ok(());
+            }
+        }
+
+        let result = self.do_refresh(id).await;
+        self.in_flight.lock().unwrap().remove(&id);
+        result
+    }
+
+    async fn do_refresh(&self, id: i64) -> Result<(), String> {
+        let account = self
+            .store
+            .get_account_by_id(id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "account not found".to_string())?;
+
+        match self.oauth.refresh_token(&account.refresh_token).await {
+            Ok(token) => {
+                let expires_at = now() + token.expires_in as i64;
+
+                // Google doesn't always rotate the refresh token; keep the
+                // existing one when the response omits it.
+                if let Some(rotated) = token.refresh_token {
+                    let mut updated = account.clone();
+                    updated.access_token = token.access_token;
+                    updated.expires_at = expires_at;
+                    updated.refresh_token = rotated;
+                    self.store.save_account(&updated).await.map_err(|e| e.to_string())?;
+                } else {
+                    self.store
+                        .update_account_tokens(id, &token.access_token, expires_at)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+
+                info!("Refreshed token for account {} ({})", id, account.email);
+                Ok(())
+            }
+            Err(err) => {
+                let message = err.to_string();
+
+                self.store.set_account_active(id, false).await.ok();
+                self.store
+                    .insert_log(&MonitorLog {
+                        id: 0,
+                        timestamp: now(),
+                        method: "OAUTH".to_string(),
+                        path: "/oauth/refresh".to_string(),
+                        status_code: 401,
+                        latency_ms: 0,
+                        account_email: Some(account.email),
+                        model: None,
+                        input_tokens: None,
+                        output_tokens: None,
+                        error_message: Some(message.clone()),
+                    })
+                    .await
+                    .ok();
+
+                Err(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Application configuration
+
+pub mod settings;
+
+pub use settings::*;
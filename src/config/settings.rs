@@ -9,6 +9,15 @@ pub const DEFAULT_PORT: u16 = 8094;
 /// Default host for the server
 pub const DEFAULT_HOST: &str = "127.0.0.1";
 
+/// Default number of pooled SQLite connections
+pub const DEFAULT_DB_POOL_SIZE: usize = 8;
+
+/// Default interval, in seconds, between token-refresh scans
+pub const DEFAULT_TOKEN_REFRESH_INTERVAL_SECS: u64 = 60;
+
+/// Default skew window, in seconds, before expiry that triggers a refresh
+pub const DEFAULT_TOKEN_REFRESH_SKEW_SECS: i64 = 300;
+
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -21,14 +30,20 @@ pub struct Settings {
     /// Database path (optional, defaults to platform-specific location)
     pub db_path: Option<PathBuf>,
 
-    /// Enable LAN access (bind to 0.0.0.0)
-    pub allow_lan_access: bool,
+    /// Number of pooled SQLite connections (ignored when using Postgres)
+    #[serde(default = "default_db_pool_size")]
+    pub db_pool_size: usize,
+
+    /// How often, in seconds, the background task scans for accounts needing a token refresh
+    #[serde(default = "default_token_refresh_interval_secs")]
+    pub token_refresh_interval_secs: u64,
 
-    /// Require API key authentication
-    pub require_auth: bool,
+    /// How long, in seconds, before expiry an account's token is refreshed
+    #[serde(default = "default_token_refresh_skew_secs")]
+    pub token_refresh_skew_secs: i64,
 
-    /// API key for authentication (if require_auth is true)
-    pub api_key: Option<String>,
+    /// Enable LAN access (bind to 0.0.0.0)
+    pub allow_lan_access: bool,
 
     /// Auto-open browser on start
     pub open_browser: bool,
@@ -37,15 +52,28 @@ pub struct Settings {
     pub log_level: String,
 }
 
+fn default_db_pool_size() -> usize {
+    DEFAULT_DB_POOL_SIZE
+}
+
+fn default_token_refresh_interval_secs() -> u64 {
+    DEFAULT_TOKEN_REFRESH_INTERVAL_SECS
+}
+
+fn default_token_refresh_skew_secs() -> i64 {
+    DEFAULT_TOKEN_REFRESH_SKEW_SECS
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
             host: DEFAULT_HOST.to_string(),
             port: DEFAULT_PORT,
             db_path: None,
+            db_pool_size: DEFAULT_DB_POOL_SIZE,
+            token_refresh_interval_secs: DEFAULT_TOKEN_REFRESH_INTERVAL_SECS,
+            token_refresh_skew_secs: DEFAULT_TOKEN_REFRESH_SKEW_SECS,
             allow_lan_access: false,
-            require_auth: false,
-            api_key: None,
             open_browser: false,
             log_level: "info".to_string(),
         }
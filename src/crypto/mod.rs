@@ -0,0 +1,146 @@
+//! Encryption at rest for stored OAuth tokens
+//!
+//! [`TokenCipher`] seals `access_token`/`refresh_token` before they reach
+//! `accounts.access_token`/`accounts.refresh_token`, so a copy of `data.db`
+//! alone no longer hands out live Google credentials. The master key lives
+//! in the OS keyring; on platforms with no keyring available it falls back
+//! to an Argon2id key derived from `BETTER_MANAGER_MASTER_PASSPHRASE`, with
+//! the salt stored next to the database. That passphrase must be set
+//! explicitly in this case - deriving from a default would let anyone who
+//! can read the database also read the salt and rebuild the same key.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use std::path::Path;
+use thiserror::Error;
+
+const KEYRING_SERVICE: &str = "better-manager";
+const KEYRING_USER: &str = "token-encryption-key";
+const SALT_FILE: &str = "encryption.salt";
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, Error)]
+pub enum CryptoError {
+    #[error("failed to seal token: {0}")]
+    Seal(String),
+
+    #[error("failed to unseal token: {0}")]
+    Unseal(String),
+
+    #[error("failed to load or create master key: {0}")]
+    KeyUnavailable(String),
+}
+
+/// Seals and unseals account tokens with a single master key
+pub struct TokenCipher {
+    cipher: XChaCha20Poly1305,
+}
+
+impl TokenCipher {
+    /// Load the master key from the OS keyring, generating one on first run;
+    /// fall back to a passphrase-derived key under `data_dir` when no
+    /// keyring is available (e.g. a headless server).
+    pub fn load(data_dir: &Path) -> Result<Self, CryptoError> {
+        let key = load_or_create_key(data_dir)?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new((&key).into()),
+        })
+    }
+
+    /// Seal a token, returning `nonce || ciphertext`, base64-encoded
+    pub fn seal(&self, plaintext: &str) -> Result<String, CryptoError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| CryptoError::Seal(e.to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    /// Unseal a token previously produced by [`Self::seal`]
+    pub fn unseal(&self, sealed: &str) -> Result<String, CryptoError> {
+        let raw = STANDARD
+            .decode(sealed)
+            .map_err(|e| CryptoError::Unseal(e.to_string()))?;
+
+        if raw.len() < NONCE_LEN {
+            return Err(CryptoError::Unseal("sealed value shorter than a nonce".into()));
+        }
+        let (nonce, ciphertext) = raw.split_at(NONCE_LEN);
+
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|e| CryptoError::Unseal(e.to_string()))?;
+
+        String::from_utf8(plaintext).map_err(|e| CryptoError::Unseal(e.to_string()))
+    }
+}
+
+fn load_or_create_key(data_dir: &Path) -> Result<[u8; 32], CryptoError> {
+    use keyring::Entry;
+
+    let entry = Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = STANDARD
+                .decode(encoded)
+                .map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+            bytes
+                .try_into()
+                .map_err(|_| CryptoError::KeyUnavailable("stored key has the wrong length".into()))
+        }
+        Err(keyring::Error::NoEntry) => {
+            let mut key = [0u8; 32];
+            getrandom::fill(&mut key).map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+            entry
+                .set_password(&STANDARD.encode(key))
+                .map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+            Ok(key)
+        }
+        // No keyring backend on this platform (common on headless servers) -
+        // fall back to a passphrase-derived key instead of failing to start
+        Err(_) => derive_key_from_passphrase(data_dir),
+    }
+}
+
+fn derive_key_from_passphrase(data_dir: &Path) -> Result<[u8; 32], CryptoError> {
+    use argon2::Argon2;
+
+    let salt_path = data_dir.join(SALT_FILE);
+    let salt = match std::fs::read(&salt_path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let mut salt = [0u8; 16];
+            getrandom::fill(&mut salt).map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+            std::fs::write(&salt_path, salt).map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+            salt.to_vec()
+        }
+    };
+
+    // Without a keyring, the daemon has no interactive prompt to ask for a
+    // passphrase. The salt lives right next to the database it protects, so
+    // deriving from anything but an operator-supplied secret would let
+    // whoever can read data.db also read the encryption key - fail closed
+    // instead.
+    let passphrase = std::env::var("BETTER_MANAGER_MASTER_PASSPHRASE").map_err(|_| {
+        CryptoError::KeyUnavailable(
+            "no OS keyring available and BETTER_MANAGER_MASTER_PASSPHRASE is unset".into(),
+        )
+    })?;
+
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| CryptoError::KeyUnavailable(e.to_string()))?;
+
+    Ok(key)
+}
@@ -1,9 +1,14 @@
 //! Account database operations
 
 use super::models::Account;
-use rusqlite::{params, Connection, OptionalExtension, Result};
+use crate::crypto::TokenCipher;
+use rusqlite::{params, Connection, Error as SqlError, OptionalExtension, Result};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+const ACCOUNT_COLUMNS: &str = "id, email, display_name, photo_url, access_token, refresh_token,
+                expires_at, is_active, sort_order, created_at, updated_at, rate_limit_rpm,
+                encryption_version";
+
 /// Get current timestamp in seconds
 fn now() -> i64 {
     SystemTime::now()
@@ -12,66 +17,86 @@ fn now() -> i64 {
         .as_secs() as i64
 }
 
-/// Get all accounts ordered by sort_order
-pub fn get_all_accounts(conn: &Connection) -> Result<Vec<Account>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, email, display_name, photo_url, access_token, refresh_token,
-                expires_at, is_active, sort_order, created_at, updated_at
-         FROM accounts ORDER BY sort_order ASC",
-    )?;
+/// Unseal a token column, passing rows written before the v4 migration
+/// through unchanged (`encryption_version = 0` means the column already
+/// holds plaintext)
+fn unseal_token(cipher: &TokenCipher, value: String, encryption_version: i32) -> Result<String> {
+    if encryption_version == 0 {
+        return Ok(value);
+    }
 
-    let accounts = stmt
-        .query_map([], |row| {
-            Ok(Account {
-                id: row.get(0)?,
-                email: row.get(1)?,
-                display_name: row.get(2)?,
-                photo_url: row.get(3)?,
-                access_token: row.get(4)?,
-                refresh_token: row.get(5)?,
-                expires_at: row.get(6)?,
-                is_active: row.get::<_, i32>(7)? != 0,
-                sort_order: row.get(8)?,
-                created_at: row.get(9)?,
-                updated_at: row.get(10)?,
-            })
-        })?
-        .collect::<Result<Vec<_>>>()?;
+    cipher
+        .unseal(&value)
+        .map_err(|e| SqlError::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+}
 
-    Ok(accounts)
+/// Maps a row to an [`Account`] plus whether its tokens are still stored in
+/// plaintext (`encryption_version = 0`), so callers can lazily re-seal it
+fn row_to_account(row: &rusqlite::Row, cipher: &TokenCipher) -> Result<(Account, bool)> {
+    let encryption_version: i32 = row.get(12)?;
+    let access_token: String = row.get(4)?;
+    let refresh_token: String = row.get(5)?;
+
+    let account = Account {
+        id: row.get(0)?,
+        email: row.get(1)?,
+        display_name: row.get(2)?,
+        photo_url: row.get(3)?,
+        access_token: unseal_token(cipher, access_token, encryption_version)?,
+        refresh_token: unseal_token(cipher, refresh_token, encryption_version)?,
+        expires_at: row.get(6)?,
+        is_active: row.get::<_, i32>(7)? != 0,
+        sort_order: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        rate_limit_rpm: row.get::<_, Option<i64>>(11)?.map(|v| v as u32),
+    };
+
+    Ok((account, encryption_version == 0))
 }
 
-/// Get account by ID
-pub fn get_account_by_id(conn: &Connection, id: i64) -> Result<Option<Account>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, email, display_name, photo_url, access_token, refresh_token,
-                expires_at, is_active, sort_order, created_at, updated_at
-         FROM accounts WHERE id = ?",
-    )?;
+/// Re-seal a plaintext (pre-v4) row's tokens in place so a copy of the
+/// database doesn't keep handing out live credentials forever just because
+/// the account was never explicitly refreshed or edited
+fn reencrypt_if_needed(conn: &Connection, account: Account, is_plaintext: bool, cipher: &TokenCipher) -> Result<Account> {
+    if is_plaintext {
+        save_account(conn, &account, cipher)?;
+    }
+    Ok(account)
+}
 
-    let account = stmt
-        .query_row([id], |row| {
-            Ok(Account {
-                id: row.get(0)?,
-                email: row.get(1)?,
-                display_name: row.get(2)?,
-                photo_url: row.get(3)?,
-                access_token: row.get(4)?,
-                refresh_token: row.get(5)?,
-                expires_at: row.get(6)?,
-                is_active: row.get::<_, i32>(7)? != 0,
-                sort_order: row.get(8)?,
-                created_at: row.get(9)?,
-                updated_at: row.get(10)?,
-            })
-        })
-        .optional()?;
+/// Get all accounts ordered by sort_order
+pub fn get_all_accounts(conn: &Connection, cipher: &TokenCipher) -> Result<Vec<Account>> {
+    let rows = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM accounts ORDER BY sort_order ASC"
+        ))?;
+        stmt.query_map([], |row| row_to_account(row, cipher))?
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    rows.into_iter()
+        .map(|(account, is_plaintext)| reencrypt_if_needed(conn, account, is_plaintext, cipher))
+        .collect()
+}
 
-    Ok(account)
+/// Get account by ID
+pub fn get_account_by_id(conn: &Connection, id: i64, cipher: &TokenCipher) -> Result<Option<Account>> {
+    let row = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM accounts WHERE id = ?"
+        ))?;
+        stmt.query_row([id], |row| row_to_account(row, cipher)).optional()?
+    };
+
+    match row {
+        Some((account, is_plaintext)) => Ok(Some(reencrypt_if_needed(conn, account, is_plaintext, cipher)?)),
+        None => Ok(None),
+    }
 }
 
 /// Get the current selected account
-pub fn get_current_account(conn: &Connection) -> Result<Option<Account>> {
+pub fn get_current_account(conn: &Connection, cipher: &TokenCipher) -> Result<Option<Account>> {
     let account_id: Option<i64> = conn
         .query_row(
             "SELECT account_id FROM current_account WHERE id = 1",
@@ -82,16 +107,23 @@ pub fn get_current_account(conn: &Connection) -> Result<Option<Account>> {
         .flatten();
 
     if let Some(id) = account_id {
-        get_account_by_id(conn, id)
+        get_account_by_id(conn, id, cipher)
     } else {
         Ok(None)
     }
 }
 
-/// Save or update an account
-pub fn save_account(conn: &Connection, account: &Account) -> Result<i64> {
+/// Save or update an account, sealing its tokens before they hit disk
+pub fn save_account(conn: &Connection, account: &Account, cipher: &TokenCipher) -> Result<i64> {
     let now = now();
 
+    let access_token = cipher
+        .seal(&account.access_token)
+        .map_err(|e| SqlError::ToSqlConversionFailure(Box::new(e)))?;
+    let refresh_token = cipher
+        .seal(&account.refresh_token)
+        .map_err(|e| SqlError::ToSqlConversionFailure(Box::new(e)))?;
+
     if account.id == 0 {
         // Insert new account
         let max_order: i32 = conn
@@ -102,19 +134,21 @@ pub fn save_account(conn: &Connection, account: &Account) -> Result<i64> {
 
         conn.execute(
             "INSERT INTO accounts (email, display_name, photo_url, access_token, refresh_token,
-                                   expires_at, is_active, sort_order, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                                   expires_at, is_active, sort_order, created_at, updated_at,
+                                   rate_limit_rpm, encryption_version)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1)",
             params![
                 account.email,
                 account.display_name,
                 account.photo_url,
-                account.access_token,
-                account.refresh_token,
+                access_token,
+                refresh_token,
                 account.expires_at,
                 account.is_active as i32,
                 max_order + 1,
                 now,
-                now
+                now,
+                account.rate_limit_rpm
             ],
         )?;
 
@@ -124,18 +158,20 @@ pub fn save_account(conn: &Connection, account: &Account) -> Result<i64> {
         conn.execute(
             "UPDATE accounts SET email = ?, display_name = ?, photo_url = ?,
                                  access_token = ?, refresh_token = ?, expires_at = ?,
-                                 is_active = ?, sort_order = ?, updated_at = ?
+                                 is_active = ?, sort_order = ?, updated_at = ?,
+                                 rate_limit_rpm = ?, encryption_version = 1
              WHERE id = ?",
             params![
                 account.email,
                 account.display_name,
                 account.photo_url,
-                account.access_token,
-                account.refresh_token,
+                access_token,
+                refresh_token,
                 account.expires_at,
                 account.is_active as i32,
                 account.sort_order,
                 now,
+                account.rate_limit_rpm,
                 account.id
             ],
         )?;
@@ -159,6 +195,34 @@ pub fn set_current_account(conn: &Connection, account_id: Option<i64>) -> Result
     Ok(())
 }
 
+/// Set a refreshed access token and its new expiry for an account
+pub fn update_tokens(
+    conn: &Connection,
+    id: i64,
+    access_token: &str,
+    expires_at: i64,
+    cipher: &TokenCipher,
+) -> Result<()> {
+    let sealed = cipher
+        .seal(access_token)
+        .map_err(|e| SqlError::ToSqlConversionFailure(Box::new(e)))?;
+
+    conn.execute(
+        "UPDATE accounts SET access_token = ?, expires_at = ?, updated_at = ?, encryption_version = 1 WHERE id = ?",
+        params![sealed, expires_at, now(), id],
+    )?;
+    Ok(())
+}
+
+/// Set an account's active flag, e.g. to deactivate it after a failed token refresh
+pub fn set_account_active(conn: &Connection, id: i64, is_active: bool) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET is_active = ?, updated_at = ? WHERE id = ?",
+        params![is_active as i32, now(), id],
+    )?;
+    Ok(())
+}
+
 /// Toggle account active status
 pub fn toggle_account_active(conn: &Connection, id: i64) -> Result<bool> {
     conn.execute(
@@ -176,30 +240,16 @@ pub fn toggle_account_active(conn: &Connection, id: i64) -> Result<bool> {
 }
 
 /// Get only active accounts
-pub fn get_active_accounts(conn: &Connection) -> Result<Vec<Account>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, email, display_name, photo_url, access_token, refresh_token,
-                expires_at, is_active, sort_order, created_at, updated_at
-         FROM accounts WHERE is_active = 1 ORDER BY sort_order ASC",
-    )?;
-
-    let accounts = stmt
-        .query_map([], |row| {
-            Ok(Account {
-                id: row.get(0)?,
-                email: row.get(1)?,
-                display_name: row.get(2)?,
-                photo_url: row.get(3)?,
-                access_token: row.get(4)?,
-                refresh_token: row.get(5)?,
-                expires_at: row.get(6)?,
-                is_active: row.get::<_, i32>(7)? != 0,
-                sort_order: row.get(8)?,
-                created_at: row.get(9)?,
-                updated_at: row.get(10)?,
-            })
-        })?
-        .collect::<Result<Vec<_>>>()?;
-
-    Ok(accounts)
+pub fn get_active_accounts(conn: &Connection, cipher: &TokenCipher) -> Result<Vec<Account>> {
+    let rows = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM accounts WHERE is_active = 1 ORDER BY sort_order ASC"
+        ))?;
+        stmt.query_map([], |row| row_to_account(row, cipher))?
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    rows.into_iter()
+        .map(|(account, is_plaintext)| reencrypt_if_needed(conn, account, is_plaintext, cipher))
+        .collect()
 }
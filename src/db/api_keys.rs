@@ -0,0 +1,59 @@
+//! API key database operations
+
+use super::models::ApiKey;
+use rusqlite::{params, Connection, Result};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn row_to_api_key(row: &rusqlite::Row) -> Result<ApiKey> {
+    Ok(ApiKey {
+        id: row.get(0)?,
+        key_hash: row.get(1)?,
+        label: row.get(2)?,
+        valid_from: row.get(3)?,
+        valid_until: row.get(4)?,
+        revoked: row.get::<_, i32>(5)? != 0,
+        created_at: row.get(6)?,
+    })
+}
+
+const API_KEY_COLUMNS: &str =
+    "id, key_hash, label, valid_from, valid_until, revoked, created_at";
+
+/// Create a new API key record. The raw key is never stored, only its hash.
+pub fn create_api_key(
+    conn: &Connection,
+    key_hash: &str,
+    label: &str,
+    valid_from: Option<i64>,
+    valid_until: Option<i64>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO api_keys (key_hash, label, valid_from, valid_until, revoked, created_at)
+         VALUES (?, ?, ?, ?, 0, ?)",
+        params![key_hash, label, valid_from, valid_until, now()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// List all API keys (revoked and expired included, for the dashboard's key
+/// management view)
+pub fn get_all_api_keys(conn: &Connection) -> Result<Vec<ApiKey>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {API_KEY_COLUMNS} FROM api_keys ORDER BY created_at DESC"
+    ))?;
+
+    stmt.query_map([], row_to_api_key)?.collect()
+}
+
+/// Revoke an API key so it's rejected regardless of its validity window
+pub fn revoke_api_key(conn: &Connection, id: i64) -> Result<bool> {
+    let rows = conn.execute("UPDATE api_keys SET revoked = 1 WHERE id = ?", [id])?;
+    Ok(rows > 0)
+}
@@ -57,7 +57,7 @@ pub fn get_proxy_config(conn: &Connection) -> Result<ProxyConfig> {
     let result: Option<ProxyConfig> = conn
         .query_row(
             "SELECT id, enabled, host, port, scheduling_mode, session_stickiness,
-                    allowed_models, api_key, created_at, updated_at
+                    allowed_models, api_key, created_at, updated_at, rate_limit_rpm
              FROM proxy_config WHERE id = 1",
             [],
             |row| {
@@ -76,6 +76,7 @@ pub fn get_proxy_config(conn: &Connection) -> Result<ProxyConfig> {
                     api_key: row.get(7)?,
                     created_at: row.get(8)?,
                     updated_at: row.get(9)?,
+                    rate_limit_rpm: row.get::<_, Option<i64>>(10)?.map(|v| v as u32),
                 })
             },
         )
@@ -99,8 +100,8 @@ pub fn save_proxy_config(conn: &Connection, config: &ProxyConfig) -> Result<()>
 
     conn.execute(
         "INSERT INTO proxy_config (id, enabled, host, port, scheduling_mode, session_stickiness,
-                                   allowed_models, api_key, created_at, updated_at)
-         VALUES (1, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                   allowed_models, api_key, created_at, updated_at, rate_limit_rpm)
+         VALUES (1, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
          ON CONFLICT(id) DO UPDATE SET
              enabled = excluded.enabled,
              host = excluded.host,
@@ -109,7 +110,8 @@ pub fn save_proxy_config(conn: &Connection, config: &ProxyConfig) -> Result<()>
              session_stickiness = excluded.session_stickiness,
              allowed_models = excluded.allowed_models,
              api_key = excluded.api_key,
-             updated_at = excluded.updated_at",
+             updated_at = excluded.updated_at,
+             rate_limit_rpm = excluded.rate_limit_rpm",
         params![
             config.enabled as i32,
             config.host,
@@ -119,7 +121,8 @@ pub fn save_proxy_config(conn: &Connection, config: &ProxyConfig) -> Result<()>
             models_json,
             config.api_key,
             now,
-            now
+            now,
+            config.rate_limit_rpm
         ],
     )?;
     Ok(())
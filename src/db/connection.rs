@@ -1,11 +1,25 @@
 //! Database connection management
+//!
+//! [`Database`] hands out connections from a small fixed pool instead of a
+//! single shared one, so a long-running dashboard query (stats, log listing)
+//! no longer blocks the proxy's own log writes. Connections are opened with
+//! WAL mode plus a busy timeout so SQLite backs off internally on the rare
+//! occasions two pooled connections still collide on the same page.
 
+use crossbeam_channel::{Receiver, Sender};
 use directories::ProjectDirs;
 use rusqlite::{Connection, Result};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, info};
 
+/// Number of pooled connections when the caller doesn't ask for a specific size
+pub const DEFAULT_POOL_SIZE: usize = 8;
+
+/// How long a connection waits on SQLite's lock before giving up with `SQLITE_BUSY`
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Get the default database path for the current platform
 pub fn get_default_db_path() -> PathBuf {
     if let Some(proj_dirs) = ProjectDirs::from("com", "nagara", "better-manager") {
@@ -18,34 +32,61 @@ pub fn get_default_db_path() -> PathBuf {
     }
 }
 
-/// Database wrapper for shared access
+fn open_connection(path: &PathBuf) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+/// Database wrapper for shared access, backed by a pool of SQLite connections
+///
+/// Cloning is cheap: the pool's checkout/checkin channels and the writer
+/// mutex are reference-counted internally, so every clone draws from the
+/// same fixed set of connections.
 #[derive(Clone)]
 pub struct Database {
-    conn: Arc<Mutex<Connection>>,
+    checkout: Sender<Connection>,
+    checkin: Receiver<Connection>,
+    /// Single connection dedicated to the hottest write path (proxy log
+    /// inserts), kept off the read pool so a burst of writes can't starve
+    /// dashboard reads waiting on the same channel.
+    writer: Arc<Mutex<Connection>>,
     path: PathBuf,
 }
 
 impl Database {
-    /// Open or create a database at the given path
+    /// Open or create a database at the given path with [`DEFAULT_POOL_SIZE`] connections
     pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        Self::open_with_pool_size(path, DEFAULT_POOL_SIZE)
+    }
+
+    /// Open or create a database at the given path with a specific number of pooled connections
+    pub fn open_with_pool_size(path: Option<PathBuf>, pool_size: usize) -> Result<Self> {
         let db_path = path.unwrap_or_else(get_default_db_path);
 
-        debug!("Opening database at: {:?}", db_path);
+        debug!("Opening database at: {:?} (pool size {})", db_path, pool_size);
 
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).ok();
         }
 
-        let conn = Connection::open(&db_path)?;
+        let (checkout, checkin) = crossbeam_channel::bounded(pool_size.max(1));
+        for _ in 0..pool_size.max(1) {
+            checkout
+                .send(open_connection(&db_path)?)
+                .expect("pool channel just created, cannot be full or disconnected");
+        }
 
-        // Enable WAL mode for better concurrency
-        conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        let writer = Arc::new(Mutex::new(open_connection(&db_path)?));
 
-        info!("Database opened: {:?}", db_path);
+        info!("Database opened: {:?} (pool size {})", db_path, pool_size);
 
         Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
+            checkout,
+            checkin,
+            writer,
             path: db_path,
         })
     }
@@ -55,21 +96,64 @@ impl Database {
         &self.path
     }
 
-    /// Execute a function with the connection
-    pub fn with_conn<F, T>(&self, f: F) -> Result<T>
+    /// Check out a pooled connection, run `f`, and return the connection to the pool
+    ///
+    /// Runs on a blocking-pool thread via `spawn_blocking` rather than the
+    /// calling task: checkout blocks until a connection is free, and doing
+    /// that on a Tokio worker thread would stall the whole async runtime
+    /// once enough concurrent callers are waiting on the same pool.
+    pub async fn with_conn<F, T>(&self, f: F) -> Result<T>
     where
-        F: FnOnce(&Connection) -> Result<T>,
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
     {
-        let conn = self.conn.lock().unwrap();
-        f(&conn)
+        let checkout = self.checkout.clone();
+        let checkin = self.checkin.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = checkin.recv().expect("pool sender cannot disconnect, Database holds one");
+            let result = f(&conn);
+            checkout.send(conn).expect("pool is never over capacity");
+            result
+        })
+        .await
+        .expect("with_conn blocking task panicked")
     }
 
     /// Execute a function with mutable connection
-    pub fn with_conn_mut<F, T>(&self, f: F) -> Result<T>
+    pub async fn with_conn_mut<F, T>(&self, f: F) -> Result<T>
     where
-        F: FnOnce(&mut Connection) -> Result<T>,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
     {
-        let mut conn = self.conn.lock().unwrap();
-        f(&mut conn)
+        let checkout = self.checkout.clone();
+        let checkin = self.checkin.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = checkin.recv().expect("pool sender cannot disconnect, Database holds one");
+            let result = f(&mut conn);
+            checkout.send(conn).expect("pool is never over capacity");
+            result
+        })
+        .await
+        .expect("with_conn_mut blocking task panicked")
+    }
+
+    /// Run `f` against the dedicated writer connection
+    ///
+    /// Use this for the hot, high-frequency write path (proxy log inserts)
+    /// so it never has to wait behind a dashboard read holding a pooled
+    /// connection, and vice versa. Like [`Self::with_conn`], this runs on a
+    /// blocking-pool thread rather than the calling task.
+    pub async fn with_writer<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let writer = self.writer.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = writer.lock().unwrap();
+            f(&conn)
+        })
+        .await
+        .expect("with_writer blocking task panicked")
     }
 }
@@ -0,0 +1,149 @@
+//! [`Store`] decorator that feeds the in-process metrics registry
+//!
+//! Wrapping the configured backend (rather than touching [`SqliteStore`] and
+//! [`PostgresStore`] individually) keeps metric recording in one place
+//! regardless of which database is in use.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::models::{Account, ApiKey, AppConfig, MonitorLog, ProxyConfig};
+use super::monitor::{LogStats, TimeseriesBucket};
+use super::store::{Store, StoreError};
+use crate::metrics::Registry;
+
+pub struct MetricsStore {
+    inner: Arc<dyn Store>,
+    registry: Arc<Registry>,
+}
+
+impl MetricsStore {
+    pub fn new(inner: Arc<dyn Store>, registry: Arc<Registry>) -> Self {
+        Self { inner, registry }
+    }
+}
+
+#[async_trait]
+impl Store for MetricsStore {
+    async fn get_all_accounts(&self) -> Result<Vec<Account>, StoreError> {
+        self.inner.get_all_accounts().await
+    }
+
+    async fn get_account_by_id(&self, id: i64) -> Result<Option<Account>, StoreError> {
+        self.inner.get_account_by_id(id).await
+    }
+
+    async fn get_active_accounts(&self) -> Result<Vec<Account>, StoreError> {
+        self.inner.get_active_accounts().await
+    }
+
+    async fn get_current_account(&self) -> Result<Option<Account>, StoreError> {
+        self.inner.get_current_account().await
+    }
+
+    async fn save_account(&self, account: &Account) -> Result<i64, StoreError> {
+        self.inner.save_account(account).await
+    }
+
+    async fn delete_account(&self, id: i64) -> Result<bool, StoreError> {
+        self.inner.delete_account(id).await
+    }
+
+    async fn set_current_account(&self, id: Option<i64>) -> Result<(), StoreError> {
+        self.inner.set_current_account(id).await
+    }
+
+    async fn toggle_account_active(&self, id: i64) -> Result<bool, StoreError> {
+        self.inner.toggle_account_active(id).await
+    }
+
+    async fn set_account_active(&self, id: i64, is_active: bool) -> Result<(), StoreError> {
+        self.inner.set_account_active(id, is_active).await
+    }
+
+    async fn update_account_tokens(
+        &self,
+        id: i64,
+        access_token: &str,
+        expires_at: i64,
+    ) -> Result<(), StoreError> {
+        self.inner.update_account_tokens(id, access_token, expires_at).await
+    }
+
+    async fn get_proxy_config(&self) -> Result<ProxyConfig, StoreError> {
+        self.inner.get_proxy_config().await
+    }
+
+    async fn save_proxy_config(&self, config: &ProxyConfig) -> Result<(), StoreError> {
+        self.inner.save_proxy_config(config).await
+    }
+
+    async fn get_app_config(&self, key: &str) -> Result<Option<String>, StoreError> {
+        self.inner.get_app_config(key).await
+    }
+
+    async fn save_app_config(&self, key: &str, value: &str) -> Result<(), StoreError> {
+        self.inner.save_app_config(key, value).await
+    }
+
+    async fn delete_app_config(&self, key: &str) -> Result<bool, StoreError> {
+        self.inner.delete_app_config(key).await
+    }
+
+    async fn get_all_app_config(&self) -> Result<Vec<AppConfig>, StoreError> {
+        self.inner.get_all_app_config().await
+    }
+
+    async fn insert_log(&self, log: &MonitorLog) -> Result<i64, StoreError> {
+        let id = self.inner.insert_log(log).await?;
+        self.registry.record(log);
+        Ok(id)
+    }
+
+    async fn get_logs(&self, limit: u32, offset: u32) -> Result<Vec<MonitorLog>, StoreError> {
+        self.inner.get_logs(limit, offset).await
+    }
+
+    async fn get_log_count(&self) -> Result<u64, StoreError> {
+        self.inner.get_log_count().await
+    }
+
+    async fn clear_logs(&self) -> Result<u64, StoreError> {
+        self.inner.clear_logs().await
+    }
+
+    async fn get_stats(&self) -> Result<LogStats, StoreError> {
+        self.inner.get_stats().await
+    }
+
+    async fn get_timeseries(
+        &self,
+        from: i64,
+        to: i64,
+        bucket_seconds: i64,
+        account_email: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Vec<TimeseriesBucket>, StoreError> {
+        self.inner
+            .get_timeseries(from, to, bucket_seconds, account_email, model)
+            .await
+    }
+
+    async fn list_api_keys(&self) -> Result<Vec<ApiKey>, StoreError> {
+        self.inner.list_api_keys().await
+    }
+
+    async fn create_api_key(
+        &self,
+        key_hash: &str,
+        label: &str,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
+    ) -> Result<i64, StoreError> {
+        self.inner.create_api_key(key_hash, label, valid_from, valid_until).await
+    }
+
+    async fn revoke_api_key(&self, id: i64) -> Result<bool, StoreError> {
+        self.inner.revoke_api_key(id).await
+    }
+}
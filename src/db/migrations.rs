@@ -1,150 +1,223 @@
-//! Database migrations
+//! Versioned, reversible SQLite schema migrations
+//!
+//! Each [`Migration`] is an ordered pair of `up`/`down` SQL, applied inside
+//! a transaction. [`migrate_to`] walks from the database's current version
+//! to a target version, applying `up` migrations forward or `down`
+//! migrations backward, and records every applied version in
+//! `schema_migrations` so the current version can be read back later.
 
 use rusqlite::{Connection, Result};
 use tracing::info;
 
-/// Current schema version
-const SCHEMA_VERSION: i32 = 1;
+/// Current schema version; always the last entry in [`MIGRATIONS`]
+pub const SCHEMA_VERSION: i32 = 4;
+
+/// A single reversible schema change
+struct Migration {
+    version: i32,
+    name: &'static str,
+    /// One or more `;`-separated statements applied against the schema left
+    /// by the previous migration's `up`
+    up: &'static str,
+    /// Statements that undo `up`, applied against the schema `up` produced
+    down: &'static str,
+}
 
-/// Run all migrations
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial schema",
+        up: "
+            CREATE TABLE accounts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                email TEXT UNIQUE NOT NULL,
+                display_name TEXT,
+                photo_url TEXT,
+                access_token TEXT NOT NULL,
+                refresh_token TEXT NOT NULL,
+                expires_at INTEGER NOT NULL,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE current_account (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                account_id INTEGER REFERENCES accounts(id) ON DELETE SET NULL
+            );
+            INSERT OR IGNORE INTO current_account (id, account_id) VALUES (1, NULL);
+            CREATE TABLE quota_info (
+                account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+                input_quota INTEGER NOT NULL DEFAULT 0,
+                input_used INTEGER NOT NULL DEFAULT 0,
+                output_quota INTEGER NOT NULL DEFAULT 0,
+                output_used INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE app_config (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            CREATE TABLE proxy_config (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                enabled INTEGER NOT NULL DEFAULT 0,
+                host TEXT NOT NULL DEFAULT '127.0.0.1',
+                port INTEGER NOT NULL DEFAULT 8094,
+                scheduling_mode TEXT NOT NULL DEFAULT 'cache-first',
+                session_stickiness INTEGER NOT NULL DEFAULT 1,
+                allowed_models TEXT NOT NULL DEFAULT '[]',
+                api_key TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE proxy_monitor_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                method TEXT NOT NULL,
+                path TEXT NOT NULL,
+                status_code INTEGER NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                account_email TEXT,
+                model TEXT,
+                input_tokens INTEGER,
+                output_tokens INTEGER,
+                error_message TEXT
+            );
+            CREATE INDEX idx_logs_timestamp ON proxy_monitor_logs(timestamp DESC);
+        ",
+        down: "
+            DROP INDEX idx_logs_timestamp;
+            DROP TABLE proxy_monitor_logs;
+            DROP TABLE proxy_config;
+            DROP TABLE app_config;
+            DROP TABLE quota_info;
+            DROP TABLE current_account;
+            DROP TABLE accounts;
+        ",
+    },
+    Migration {
+        version: 2,
+        name: "rate limiting",
+        up: "
+            ALTER TABLE proxy_config ADD COLUMN rate_limit_rpm INTEGER;
+            ALTER TABLE accounts ADD COLUMN rate_limit_rpm INTEGER;
+        ",
+        down: "
+            ALTER TABLE proxy_config DROP COLUMN rate_limit_rpm;
+            ALTER TABLE accounts DROP COLUMN rate_limit_rpm;
+        ",
+    },
+    Migration {
+        version: 3,
+        name: "API keys",
+        up: "
+            CREATE TABLE api_keys (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key_hash TEXT UNIQUE NOT NULL,
+                label TEXT NOT NULL,
+                valid_from INTEGER,
+                valid_until INTEGER,
+                revoked INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            );
+        ",
+        down: "DROP TABLE api_keys;",
+    },
+    Migration {
+        version: 4,
+        name: "token encryption",
+        up: "ALTER TABLE accounts ADD COLUMN encryption_version INTEGER NOT NULL DEFAULT 0;",
+        down: "ALTER TABLE accounts DROP COLUMN encryption_version;",
+    },
+];
+
+/// Run every migration up to [`SCHEMA_VERSION`]
 pub fn run_migrations(conn: &Connection) -> Result<()> {
-    let current_version = get_schema_version(conn)?;
-
-    if current_version < SCHEMA_VERSION {
-        info!("Running database migrations (v{} -> v{})", current_version, SCHEMA_VERSION);
-    }
+    migrate_to(conn, SCHEMA_VERSION)
+}
 
-    if current_version < 1 {
-        migrate_v1(conn)?;
+/// Migrate the database to a specific version, forward or backward
+///
+/// Each step runs inside its own transaction, so a failing migration leaves
+/// the schema (and `schema_migrations`) at the last version that applied
+/// cleanly rather than half-migrated.
+pub fn migrate_to(conn: &Connection, target: i32) -> Result<()> {
+    let current = get_current_version(conn)?;
+
+    if current < target {
+        info!("Running migrations (v{current} -> v{target})");
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current && m.version <= target) {
+            apply_up(conn, migration)?;
+        }
+    } else if current > target {
+        info!("Reverting migrations (v{current} -> v{target})");
+        for migration in MIGRATIONS.iter().rev().filter(|m| m.version <= current && m.version > target) {
+            apply_down(conn, migration)?;
+        }
     }
 
     Ok(())
 }
 
-/// Get the current schema version
-fn get_schema_version(conn: &Connection) -> Result<i32> {
-    // Create version table if not exists
+fn get_current_version(conn: &Connection) -> Result<i32> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS schema_version (
-            version INTEGER PRIMARY KEY
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at INTEGER NOT NULL
         )",
         [],
     )?;
 
-    let version: i32 = conn
-        .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
-        .unwrap_or(0);
-
-    Ok(version)
-}
-
-/// Set the schema version
-fn set_schema_version(conn: &Connection, version: i32) -> Result<()> {
-    conn.execute("DELETE FROM schema_version", [])?;
-    conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
-    Ok(())
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
 }
 
-/// Migration v1: Initial schema
-fn migrate_v1(conn: &Connection) -> Result<()> {
-    info!("Applying migration v1: Initial schema");
+fn apply_up(conn: &Connection, migration: &Migration) -> Result<()> {
+    info!("Applying migration v{}: {}", migration.version, migration.name);
 
-    // Accounts table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS accounts (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            email TEXT UNIQUE NOT NULL,
-            display_name TEXT,
-            photo_url TEXT,
-            access_token TEXT NOT NULL,
-            refresh_token TEXT NOT NULL,
-            expires_at INTEGER NOT NULL,
-            is_active INTEGER NOT NULL DEFAULT 1,
-            sort_order INTEGER NOT NULL DEFAULT 0,
-            created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL
-        )",
-        [],
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(migration.up)?;
+    tx.execute(
+        "INSERT INTO schema_migrations (version, applied_at) VALUES (?, unixepoch())",
+        [migration.version],
     )?;
+    tx.commit()?;
 
-    // Current account table (which account is "selected")
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS current_account (
-            id INTEGER PRIMARY KEY CHECK (id = 1),
-            account_id INTEGER REFERENCES accounts(id) ON DELETE SET NULL
-        )",
-        [],
-    )?;
-    conn.execute(
-        "INSERT OR IGNORE INTO current_account (id, account_id) VALUES (1, NULL)",
-        [],
-    )?;
+    Ok(())
+}
 
-    // Quota info table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS quota_info (
-            account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
-            input_quota INTEGER NOT NULL DEFAULT 0,
-            input_used INTEGER NOT NULL DEFAULT 0,
-            output_quota INTEGER NOT NULL DEFAULT 0,
-            output_used INTEGER NOT NULL DEFAULT 0,
-            updated_at INTEGER NOT NULL
-        )",
-        [],
-    )?;
+fn apply_down(conn: &Connection, migration: &Migration) -> Result<()> {
+    info!("Reverting migration v{}: {}", migration.version, migration.name);
 
-    // App config key-value store
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS app_config (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    )?;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(migration.down)?;
+    tx.execute("DELETE FROM schema_migrations WHERE version = ?", [migration.version])?;
+    tx.commit()?;
 
-    // Proxy config
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS proxy_config (
-            id INTEGER PRIMARY KEY CHECK (id = 1),
-            enabled INTEGER NOT NULL DEFAULT 0,
-            host TEXT NOT NULL DEFAULT '127.0.0.1',
-            port INTEGER NOT NULL DEFAULT 8094,
-            scheduling_mode TEXT NOT NULL DEFAULT 'cache-first',
-            session_stickiness INTEGER NOT NULL DEFAULT 1,
-            allowed_models TEXT NOT NULL DEFAULT '[]',
-            api_key TEXT,
-            created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL
-        )",
-        [],
-    )?;
+    Ok(())
+}
 
-    // Proxy monitor logs
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS proxy_monitor_logs (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            method TEXT NOT NULL,
-            path TEXT NOT NULL,
-            status_code INTEGER NOT NULL,
-            latency_ms INTEGER NOT NULL,
-            account_email TEXT,
-            model TEXT,
-            input_tokens INTEGER,
-            output_tokens INTEGER,
-            error_message TEXT
-        )",
-        [],
-    )?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Index for faster log queries
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_logs_timestamp ON proxy_monitor_logs(timestamp DESC)",
-        [],
-    )?;
+    /// Migrates a fresh in-memory database up to `SCHEMA_VERSION`, all the
+    /// way back down to 0, and up again, exercising every migration's `down`
+    /// SQL (including the `DROP COLUMN` steps) and not just `up`.
+    #[test]
+    fn migrate_up_down_up_round_trips() {
+        let conn = Connection::open_in_memory().unwrap();
 
-    set_schema_version(conn, 1)?;
-    info!("Migration v1 complete");
+        migrate_to(&conn, SCHEMA_VERSION).unwrap();
+        assert_eq!(get_current_version(&conn).unwrap(), SCHEMA_VERSION);
 
-    Ok(())
+        migrate_to(&conn, 0).unwrap();
+        assert_eq!(get_current_version(&conn).unwrap(), 0);
+
+        migrate_to(&conn, SCHEMA_VERSION).unwrap();
+        assert_eq!(get_current_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
 }
@@ -1,21 +1,35 @@
 //! Database module for SQLite operations
 
 mod accounts;
+mod api_keys;
 mod config;
 mod connection;
+mod metrics_store;
 mod migrations;
 mod models;
 mod monitor;
+mod postgres_store;
+mod sqlite_store;
+mod store;
 
 pub use accounts::{
     delete_account, get_account_by_id, get_active_accounts, get_all_accounts,
-    get_current_account, save_account, set_current_account, toggle_account_active,
+    get_current_account, save_account, set_account_active, set_current_account,
+    toggle_account_active, update_tokens,
 };
+pub use api_keys::{create_api_key, get_all_api_keys, revoke_api_key};
 pub use config::{
     delete_app_config, get_all_app_config, get_app_config, get_proxy_config, save_app_config,
     save_proxy_config,
 };
-pub use connection::{get_default_db_path, Database};
-pub use migrations::run_migrations;
-pub use models::{Account, AppConfig, MonitorLog, ProxyConfig, QuotaInfo};
-pub use monitor::{clear_logs, get_log_count, get_logs, get_stats, insert_log, LogStats};
+pub use connection::{get_default_db_path, Database, DEFAULT_POOL_SIZE};
+pub use metrics_store::MetricsStore;
+pub use migrations::{migrate_to, run_migrations, SCHEMA_VERSION};
+pub use models::{Account, ApiKey, AppConfig, MonitorLog, ProxyConfig, QuotaInfo};
+pub use monitor::{
+    clear_logs, get_log_count, get_logs, get_stats, get_timeseries, insert_log, LogStats,
+    TimeseriesBucket,
+};
+pub use postgres_store::PostgresStore;
+pub use sqlite_store::SqliteStore;
+pub use store::{Store, StoreError};
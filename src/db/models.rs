@@ -1,6 +1,7 @@
 //! Database models for application data
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Represents a Google account with OAuth tokens
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +17,9 @@ pub struct Account {
     pub sort_order: i32,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Per-account requests-per-minute override; falls back to
+    /// `ProxyConfig.rate_limit_rpm` when unset
+    pub rate_limit_rpm: Option<u32>,
 }
 
 /// Quota information for an account
@@ -30,7 +34,7 @@ pub struct QuotaInfo {
 }
 
 /// Proxy server configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ProxyConfig {
     pub id: i64,
     pub enabled: bool,
@@ -40,6 +44,9 @@ pub struct ProxyConfig {
     pub session_stickiness: bool,
     pub allowed_models: Vec<String>,
     pub api_key: Option<String>,
+    /// Default requests-per-minute limit applied to callers without a
+    /// per-account override; `None` leaves those callers unthrottled
+    pub rate_limit_rpm: Option<u32>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -55,6 +62,7 @@ impl Default for ProxyConfig {
             session_stickiness: true,
             allowed_models: vec![],
             api_key: None,
+            rate_limit_rpm: None,
             created_at: 0,
             updated_at: 0,
         }
@@ -62,7 +70,7 @@ impl Default for ProxyConfig {
 }
 
 /// Log entry for proxy requests
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MonitorLog {
     pub id: i64,
     pub timestamp: i64,
@@ -83,3 +91,18 @@ pub struct AppConfig {
     pub key: String,
     pub value: String,
 }
+
+/// A rotating API key accepted by the `/api` auth middleware
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: i64,
+    /// SHA-256 hex digest of the raw key; the raw key itself is never stored
+    pub key_hash: String,
+    pub label: String,
+    /// Key is rejected before this time (unix seconds), if set
+    pub valid_from: Option<i64>,
+    /// Key is rejected after this time (unix seconds), if set
+    pub valid_until: Option<i64>,
+    pub revoked: bool,
+    pub created_at: i64,
+}
@@ -1,7 +1,7 @@
 //! Monitor/logging database operations
 
 use super::models::MonitorLog;
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, Result, ToSql};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Get current timestamp in seconds
@@ -147,7 +147,7 @@ pub fn get_stats(conn: &Connection) -> Result<LogStats> {
 }
 
 /// Log statistics summary
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
 pub struct LogStats {
     pub total_requests: u64,
     pub success_count: u64,
@@ -156,3 +156,91 @@ pub struct LogStats {
     pub total_input_tokens: i64,
     pub total_output_tokens: i64,
 }
+
+/// Aggregate stats for a single time bucket, as returned by
+/// [`get_timeseries`]
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct TimeseriesBucket {
+    pub bucket_start: i64,
+    pub request_count: u64,
+    pub error_count: u64,
+    pub p50_latency_ms: u32,
+    pub p95_latency_ms: u32,
+    pub total_input_tokens: i64,
+    pub total_output_tokens: i64,
+}
+
+/// Request counts, error counts, latency percentiles and token sums grouped
+/// into fixed-size time buckets, optionally filtered by account/model.
+///
+/// Bucketing and the WHERE clause happen in one `GROUP BY` query; SQLite has
+/// no percentile aggregate, so each bucket's latencies are collected with
+/// `GROUP_CONCAT` and the p50/p95 are computed afterwards.
+pub fn get_timeseries(
+    conn: &Connection,
+    from: i64,
+    to: i64,
+    bucket_seconds: i64,
+    account_email: Option<&str>,
+    model: Option<&str>,
+) -> Result<Vec<TimeseriesBucket>> {
+    let mut sql = String::from(
+        "SELECT
+             timestamp - (timestamp % ?) AS bucket_start,
+             COUNT(*) AS request_count,
+             COUNT(*) FILTER (WHERE status_code >= 400) AS error_count,
+             COALESCE(SUM(input_tokens), 0) AS total_input_tokens,
+             COALESCE(SUM(output_tokens), 0) AS total_output_tokens,
+             GROUP_CONCAT(latency_ms) AS latencies
+         FROM proxy_monitor_logs
+         WHERE timestamp >= ? AND timestamp <= ?",
+    );
+
+    let mut query_params: Vec<Box<dyn ToSql>> =
+        vec![Box::new(bucket_seconds), Box::new(from), Box::new(to)];
+
+    if let Some(email) = account_email {
+        sql.push_str(" AND account_email = ?");
+        query_params.push(Box::new(email.to_string()));
+    }
+    if let Some(model) = model {
+        sql.push_str(" AND model = ?");
+        query_params.push(Box::new(model.to_string()));
+    }
+
+    sql.push_str(" GROUP BY bucket_start ORDER BY bucket_start ASC");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let param_refs: Vec<&dyn ToSql> = query_params.iter().map(|p| p.as_ref()).collect();
+
+    stmt.query_map(param_refs.as_slice(), |row| {
+        let latencies: Option<String> = row.get(5)?;
+        let mut sorted_latencies: Vec<u32> = latencies
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|v| v.parse().ok())
+            .collect();
+        sorted_latencies.sort_unstable();
+
+        Ok(TimeseriesBucket {
+            bucket_start: row.get(0)?,
+            request_count: row.get(1)?,
+            error_count: row.get(2)?,
+            total_input_tokens: row.get(3)?,
+            total_output_tokens: row.get(4)?,
+            p50_latency_ms: percentile(&sorted_latencies, 0.50),
+            p95_latency_ms: percentile(&sorted_latencies, 0.95),
+        })
+    })?
+    .collect()
+}
+
+/// Percentile of an already-sorted slice: the element at index
+/// `round((len - 1) * p)`
+fn percentile(sorted: &[u32], p: f64) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let idx = (((sorted.len() - 1) as f64) * p).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
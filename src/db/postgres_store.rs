@@ -0,0 +1,659 @@
+//! PostgreSQL-backed [`Store`] implementation
+//!
+//! Schema mirrors the SQLite tables created by [`super::migrations::run_migrations`]
+//! (including `api_keys`) so the two backends stay interchangeable; see
+//! `migrations/postgres.sql` for the equivalent `CREATE TABLE` statements.
+//! [`PostgresStore::connect`] applies that file on every connect, so a fresh
+//! database is usable immediately.
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_postgres::NoTls;
+
+use super::models::{Account, ApiKey, AppConfig, MonitorLog, ProxyConfig};
+use super::monitor::{LogStats, TimeseriesBucket};
+use super::store::{Store, StoreError};
+use crate::crypto::TokenCipher;
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Schema applied against a fresh Postgres database on every connect; every
+/// statement is idempotent so re-running it against an already-migrated
+/// database is a no-op.
+const POSTGRES_SCHEMA: &str = include_str!("../../migrations/postgres.sql");
+
+/// [`Store`] implementation backed by a pooled `tokio-postgres` connection
+pub struct PostgresStore {
+    pool: Pool,
+    cipher: Arc<TokenCipher>,
+}
+
+impl PostgresStore {
+    /// Connect to Postgres using a `postgres://` connection URL
+    pub async fn connect(database_url: &str, cipher: Arc<TokenCipher>) -> Result<Self, StoreError> {
+        let pg_config: tokio_postgres::Config = database_url
+            .parse()
+            .map_err(|e: tokio_postgres::Error| StoreError::Backend(e.to_string()))?;
+
+        let mgr = deadpool_postgres::Manager::new(pg_config, NoTls);
+        let pool = Pool::builder(mgr)
+            .max_size(16)
+            .build()
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        let store = Self { pool, cipher };
+        store
+            .client()
+            .await?
+            .batch_execute(POSTGRES_SCHEMA)
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        Ok(store)
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Object, StoreError> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))
+    }
+
+    /// Re-seal a plaintext (pre-v4) row's tokens in place so a copy of the
+    /// database doesn't keep handing out live credentials forever just
+    /// because the account was never explicitly refreshed or edited
+    async fn reencrypt_if_needed(&self, account: Account, is_plaintext: bool) -> Result<Account, StoreError> {
+        if is_plaintext {
+            self.save_account(&account).await?;
+        }
+        Ok(account)
+    }
+}
+
+/// Unseal a token column, passing rows written before the v4 migration
+/// through unchanged (`encryption_version = 0` means the column already
+/// holds plaintext)
+fn unseal_token(cipher: &TokenCipher, value: String, encryption_version: i32) -> Result<String, StoreError> {
+    if encryption_version == 0 {
+        return Ok(value);
+    }
+
+    cipher.unseal(&value).map_err(|e| StoreError::Backend(e.to_string()))
+}
+
+/// Maps a row to an [`Account`] plus whether its tokens are still stored in
+/// plaintext (`encryption_version = 0`), so callers can lazily re-seal it
+fn row_to_account(row: &tokio_postgres::Row, cipher: &TokenCipher) -> Result<(Account, bool), StoreError> {
+    let encryption_version: i32 = row.get("encryption_version");
+    let access_token: String = row.get("access_token");
+    let refresh_token: String = row.get("refresh_token");
+
+    let account = Account {
+        id: row.get("id"),
+        email: row.get("email"),
+        display_name: row.get("display_name"),
+        photo_url: row.get("photo_url"),
+        access_token: unseal_token(cipher, access_token, encryption_version)?,
+        refresh_token: unseal_token(cipher, refresh_token, encryption_version)?,
+        expires_at: row.get("expires_at"),
+        is_active: row.get("is_active"),
+        sort_order: row.get("sort_order"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        rate_limit_rpm: row.get::<_, Option<i32>>("rate_limit_rpm").map(|v| v as u32),
+    };
+
+    Ok((account, encryption_version == 0))
+}
+
+fn row_to_api_key(row: &tokio_postgres::Row) -> ApiKey {
+    ApiKey {
+        id: row.get("id"),
+        key_hash: row.get("key_hash"),
+        label: row.get("label"),
+        valid_from: row.get("valid_from"),
+        valid_until: row.get("valid_until"),
+        revoked: row.get("revoked"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn row_to_log(row: &tokio_postgres::Row) -> MonitorLog {
+    MonitorLog {
+        id: row.get("id"),
+        timestamp: row.get("timestamp"),
+        method: row.get("method"),
+        path: row.get("path"),
+        status_code: row.get::<_, i32>("status_code") as u16,
+        latency_ms: row.get::<_, i32>("latency_ms") as u32,
+        account_email: row.get("account_email"),
+        model: row.get("model"),
+        input_tokens: row.get("input_tokens"),
+        output_tokens: row.get("output_tokens"),
+        error_message: row.get("error_message"),
+    }
+}
+
+const ACCOUNT_COLUMNS: &str = "id, email, display_name, photo_url, access_token, refresh_token, \
+     expires_at, is_active, sort_order, created_at, updated_at, rate_limit_rpm, encryption_version";
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn get_all_accounts(&self) -> Result<Vec<Account>, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                &format!("SELECT {ACCOUNT_COLUMNS} FROM accounts ORDER BY sort_order ASC"),
+                &[],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        let mut accounts = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let (account, is_plaintext) = row_to_account(row, &self.cipher)?;
+            accounts.push(self.reencrypt_if_needed(account, is_plaintext).await?);
+        }
+        Ok(accounts)
+    }
+
+    async fn get_account_by_id(&self, id: i64) -> Result<Option<Account>, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                &format!("SELECT {ACCOUNT_COLUMNS} FROM accounts WHERE id = $1"),
+                &[&id],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        match row {
+            Some(row) => {
+                let (account, is_plaintext) = row_to_account(&row, &self.cipher)?;
+                Ok(Some(self.reencrypt_if_needed(account, is_plaintext).await?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn get_active_accounts(&self) -> Result<Vec<Account>, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {ACCOUNT_COLUMNS} FROM accounts WHERE is_active ORDER BY sort_order ASC"
+                ),
+                &[],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        let mut accounts = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let (account, is_plaintext) = row_to_account(row, &self.cipher)?;
+            accounts.push(self.reencrypt_if_needed(account, is_plaintext).await?);
+        }
+        Ok(accounts)
+    }
+
+    async fn get_current_account(&self) -> Result<Option<Account>, StoreError> {
+        let client = self.client().await?;
+        let id: Option<i64> = client
+            .query_opt("SELECT account_id FROM current_account WHERE id = 1", &[])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?
+            .and_then(|row| row.get(0));
+
+        match id {
+            Some(id) => self.get_account_by_id(id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn save_account(&self, account: &Account) -> Result<i64, StoreError> {
+        let client = self.client().await?;
+
+        let rate_limit_rpm = account.rate_limit_rpm.map(|v| v as i32);
+        let access_token = self
+            .cipher
+            .seal(&account.access_token)
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        let refresh_token = self
+            .cipher
+            .seal(&account.refresh_token)
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        let ts = now();
+
+        if account.id == 0 {
+            let row = client
+                .query_one(
+                    "INSERT INTO accounts (email, display_name, photo_url, access_token,
+                         refresh_token, expires_at, is_active, sort_order, created_at, updated_at,
+                         rate_limit_rpm, encryption_version)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7,
+                             COALESCE((SELECT MAX(sort_order) + 1 FROM accounts), 0), $8, $8, $9, 1)
+                     RETURNING id",
+                    &[
+                        &account.email,
+                        &account.display_name,
+                        &account.photo_url,
+                        &access_token,
+                        &refresh_token,
+                        &account.expires_at,
+                        &account.is_active,
+                        &ts,
+                        &rate_limit_rpm,
+                    ],
+                )
+                .await
+                .map_err(|e| StoreError::Backend(e.to_string()))?;
+            Ok(row.get(0))
+        } else {
+            client
+                .execute(
+                    "UPDATE accounts SET email = $1, display_name = $2, photo_url = $3,
+                         access_token = $4, refresh_token = $5, expires_at = $6,
+                         is_active = $7, sort_order = $8, updated_at = $9, rate_limit_rpm = $10,
+                         encryption_version = 1
+                     WHERE id = $11",
+                    &[
+                        &account.email,
+                        &account.display_name,
+                        &account.photo_url,
+                        &access_token,
+                        &refresh_token,
+                        &account.expires_at,
+                        &account.is_active,
+                        &account.sort_order,
+                        &ts,
+                        &rate_limit_rpm,
+                        &account.id,
+                    ],
+                )
+                .await
+                .map_err(|e| StoreError::Backend(e.to_string()))?;
+            Ok(account.id)
+        }
+    }
+
+    async fn delete_account(&self, id: i64) -> Result<bool, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .execute("DELETE FROM accounts WHERE id = $1", &[&id])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(rows > 0)
+    }
+
+    async fn set_current_account(&self, id: Option<i64>) -> Result<(), StoreError> {
+        let client = self.client().await?;
+        client
+            .execute(
+                "UPDATE current_account SET account_id = $1 WHERE id = 1",
+                &[&id],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn toggle_account_active(&self, id: i64) -> Result<bool, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_one(
+                "UPDATE accounts SET is_active = NOT is_active WHERE id = $1 RETURNING is_active",
+                &[&id],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(row.get(0))
+    }
+
+    async fn set_account_active(&self, id: i64, is_active: bool) -> Result<(), StoreError> {
+        let client = self.client().await?;
+        client
+            .execute(
+                "UPDATE accounts SET is_active = $1 WHERE id = $2",
+                &[&is_active, &id],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn update_account_tokens(
+        &self,
+        id: i64,
+        access_token: &str,
+        expires_at: i64,
+    ) -> Result<(), StoreError> {
+        let client = self.client().await?;
+        let sealed = self
+            .cipher
+            .seal(access_token)
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        client
+            .execute(
+                "UPDATE accounts SET access_token = $1, expires_at = $2, encryption_version = 1 WHERE id = $3",
+                &[&sealed, &expires_at, &id],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_proxy_config(&self) -> Result<ProxyConfig, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, enabled, host, port, scheduling_mode, session_stickiness,
+                        allowed_models, api_key, created_at, updated_at, rate_limit_rpm
+                 FROM proxy_config WHERE id = 1",
+                &[],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        match row {
+            Some(row) => {
+                let models_json: String = row.get("allowed_models");
+                Ok(ProxyConfig {
+                    id: row.get("id"),
+                    enabled: row.get("enabled"),
+                    host: row.get("host"),
+                    port: row.get::<_, i32>("port") as u16,
+                    scheduling_mode: row.get("scheduling_mode"),
+                    session_stickiness: row.get("session_stickiness"),
+                    allowed_models: serde_json::from_str(&models_json).unwrap_or_default(),
+                    api_key: row.get("api_key"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    rate_limit_rpm: row
+                        .get::<_, Option<i32>>("rate_limit_rpm")
+                        .map(|v| v as u32),
+                })
+            }
+            None => {
+                let config = ProxyConfig::default();
+                self.save_proxy_config(&config).await?;
+                Ok(config)
+            }
+        }
+    }
+
+    async fn save_proxy_config(&self, config: &ProxyConfig) -> Result<(), StoreError> {
+        let client = self.client().await?;
+        let models_json = serde_json::to_string(&config.allowed_models).unwrap_or_default();
+        let rate_limit_rpm = config.rate_limit_rpm.map(|v| v as i32);
+        client
+            .execute(
+                "INSERT INTO proxy_config (id, enabled, host, port, scheduling_mode,
+                     session_stickiness, allowed_models, api_key, created_at, updated_at,
+                     rate_limit_rpm)
+                 VALUES (1, $1, $2, $3, $4, $5, $6, $7, $8, $8, $9)
+                 ON CONFLICT (id) DO UPDATE SET
+                     enabled = excluded.enabled,
+                     host = excluded.host,
+                     port = excluded.port,
+                     scheduling_mode = excluded.scheduling_mode,
+                     session_stickiness = excluded.session_stickiness,
+                     allowed_models = excluded.allowed_models,
+                     api_key = excluded.api_key,
+                     updated_at = excluded.updated_at,
+                     rate_limit_rpm = excluded.rate_limit_rpm",
+                &[
+                    &config.enabled,
+                    &config.host,
+                    &(config.port as i32),
+                    &config.scheduling_mode,
+                    &config.session_stickiness,
+                    &models_json,
+                    &config.api_key,
+                    &config.updated_at,
+                    &rate_limit_rpm,
+                ],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_app_config(&self, key: &str) -> Result<Option<String>, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt("SELECT value FROM app_config WHERE key = $1", &[&key])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(row.map(|r| r.get(0)))
+    }
+
+    async fn save_app_config(&self, key: &str, value: &str) -> Result<(), StoreError> {
+        let client = self.client().await?;
+        client
+            .execute(
+                "INSERT INTO app_config (key, value) VALUES ($1, $2)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                &[&key, &value],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn delete_app_config(&self, key: &str) -> Result<bool, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .execute("DELETE FROM app_config WHERE key = $1", &[&key])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(rows > 0)
+    }
+
+    async fn get_all_app_config(&self) -> Result<Vec<AppConfig>, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .query("SELECT key, value FROM app_config", &[])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|row| AppConfig {
+                key: row.get(0),
+                value: row.get(1),
+            })
+            .collect())
+    }
+
+    async fn insert_log(&self, log: &MonitorLog) -> Result<i64, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_one(
+                "INSERT INTO proxy_monitor_logs (timestamp, method, path, status_code, latency_ms,
+                     account_email, model, input_tokens, output_tokens, error_message)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 RETURNING id",
+                &[
+                    &log.timestamp,
+                    &log.method,
+                    &log.path,
+                    &(log.status_code as i32),
+                    &(log.latency_ms as i32),
+                    &log.account_email,
+                    &log.model,
+                    &log.input_tokens,
+                    &log.output_tokens,
+                    &log.error_message,
+                ],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(row.get(0))
+    }
+
+    async fn get_logs(&self, limit: u32, offset: u32) -> Result<Vec<MonitorLog>, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                "SELECT id, timestamp, method, path, status_code, latency_ms,
+                        account_email, model, input_tokens, output_tokens, error_message
+                 FROM proxy_monitor_logs
+                 ORDER BY timestamp DESC
+                 LIMIT $1 OFFSET $2",
+                &[&(limit as i64), &(offset as i64)],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(rows.iter().map(row_to_log).collect())
+    }
+
+    async fn get_log_count(&self) -> Result<u64, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_one("SELECT COUNT(*) FROM proxy_monitor_logs", &[])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    async fn clear_logs(&self) -> Result<u64, StoreError> {
+        let count = self.get_log_count().await?;
+        let client = self.client().await?;
+        client
+            .execute("DELETE FROM proxy_monitor_logs", &[])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(count)
+    }
+
+    async fn get_stats(&self) -> Result<LogStats, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_one(
+                "SELECT
+                     COUNT(*),
+                     COUNT(*) FILTER (WHERE status_code < 400),
+                     COUNT(*) FILTER (WHERE status_code >= 400),
+                     COALESCE(AVG(latency_ms), 0),
+                     COALESCE(SUM(input_tokens), 0),
+                     COALESCE(SUM(output_tokens), 0)
+                 FROM proxy_monitor_logs",
+                &[],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        Ok(LogStats {
+            total_requests: row.get::<_, i64>(0) as u64,
+            success_count: row.get::<_, i64>(1) as u64,
+            error_count: row.get::<_, i64>(2) as u64,
+            avg_latency_ms: row.get::<_, f64>(3) as u32,
+            total_input_tokens: row.get(4),
+            total_output_tokens: row.get(5),
+        })
+    }
+
+    async fn get_timeseries(
+        &self,
+        from: i64,
+        to: i64,
+        bucket_seconds: i64,
+        account_email: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Vec<TimeseriesBucket>, StoreError> {
+        let client = self.client().await?;
+
+        let mut sql = String::from(
+            "SELECT
+                 timestamp - (timestamp % $1) AS bucket_start,
+                 COUNT(*) AS request_count,
+                 COUNT(*) FILTER (WHERE status_code >= 400) AS error_count,
+                 COALESCE(SUM(input_tokens), 0) AS total_input_tokens,
+                 COALESCE(SUM(output_tokens), 0) AS total_output_tokens,
+                 COALESCE(percentile_cont(0.5) WITHIN GROUP (ORDER BY latency_ms), 0) AS p50,
+                 COALESCE(percentile_cont(0.95) WITHIN GROUP (ORDER BY latency_ms), 0) AS p95
+             FROM proxy_monitor_logs
+             WHERE timestamp >= $2 AND timestamp <= $3",
+        );
+
+        let mut query_params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+            vec![&bucket_seconds, &from, &to];
+
+        if let Some(email) = &account_email {
+            sql.push_str(&format!(" AND account_email = ${}", query_params.len() + 1));
+            query_params.push(email);
+        }
+        if let Some(model) = &model {
+            sql.push_str(&format!(" AND model = ${}", query_params.len() + 1));
+            query_params.push(model);
+        }
+
+        sql.push_str(" GROUP BY bucket_start ORDER BY bucket_start ASC");
+
+        let rows = client
+            .query(&sql, &query_params)
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| TimeseriesBucket {
+                bucket_start: row.get("bucket_start"),
+                request_count: row.get::<_, i64>("request_count") as u64,
+                error_count: row.get::<_, i64>("error_count") as u64,
+                p50_latency_ms: row.get::<_, f64>("p50") as u32,
+                p95_latency_ms: row.get::<_, f64>("p95") as u32,
+                total_input_tokens: row.get("total_input_tokens"),
+                total_output_tokens: row.get("total_output_tokens"),
+            })
+            .collect())
+    }
+
+    async fn list_api_keys(&self) -> Result<Vec<ApiKey>, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                "SELECT id, key_hash, label, valid_from, valid_until, revoked, created_at
+                 FROM api_keys ORDER BY created_at DESC",
+                &[],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(rows.iter().map(row_to_api_key).collect())
+    }
+
+    async fn create_api_key(
+        &self,
+        key_hash: &str,
+        label: &str,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
+    ) -> Result<i64, StoreError> {
+        let client = self.client().await?;
+        let row = client
+            .query_one(
+                "INSERT INTO api_keys (key_hash, label, valid_from, valid_until, revoked, created_at)
+                 VALUES ($1, $2, $3, $4, FALSE, $5)
+                 RETURNING id",
+                &[&key_hash, &label, &valid_from, &valid_until, &now()],
+            )
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(row.get(0))
+    }
+
+    async fn revoke_api_key(&self, id: i64) -> Result<bool, StoreError> {
+        let client = self.client().await?;
+        let rows = client
+            .execute("UPDATE api_keys SET revoked = TRUE WHERE id = $1", &[&id])
+            .await
+            .map_err(|e| StoreError::Backend(e.to_string()))?;
+        Ok(rows > 0)
+    }
+}
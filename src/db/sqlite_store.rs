@@ -0,0 +1,188 @@
+//! SQLite-backed [`Store`] implementation
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::connection::Database;
+use super::models::{Account, ApiKey, AppConfig, MonitorLog, ProxyConfig};
+use super::monitor::{LogStats, TimeseriesBucket};
+use super::store::{Store, StoreError};
+use super::{accounts, api_keys, config, monitor};
+use crate::crypto::TokenCipher;
+
+/// [`Store`] implementation backed by the existing pooled SQLite [`Database`]
+pub struct SqliteStore {
+    db: Database,
+    cipher: Arc<TokenCipher>,
+}
+
+impl SqliteStore {
+    pub fn new(db: Database, cipher: Arc<TokenCipher>) -> Self {
+        Self { db, cipher }
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn get_all_accounts(&self) -> Result<Vec<Account>, StoreError> {
+        let cipher = self.cipher.clone();
+        Ok(self.db.with_conn(move |conn| accounts::get_all_accounts(conn, &cipher)).await?)
+    }
+
+    async fn get_account_by_id(&self, id: i64) -> Result<Option<Account>, StoreError> {
+        let cipher = self.cipher.clone();
+        Ok(self
+            .db
+            .with_conn(move |conn| accounts::get_account_by_id(conn, id, &cipher))
+            .await?)
+    }
+
+    async fn get_active_accounts(&self) -> Result<Vec<Account>, StoreError> {
+        let cipher = self.cipher.clone();
+        Ok(self
+            .db
+            .with_conn(move |conn| accounts::get_active_accounts(conn, &cipher))
+            .await?)
+    }
+
+    async fn get_current_account(&self) -> Result<Option<Account>, StoreError> {
+        let cipher = self.cipher.clone();
+        Ok(self.db.with_conn(move |conn| accounts::get_current_account(conn, &cipher)).await?)
+    }
+
+    async fn save_account(&self, account: &Account) -> Result<i64, StoreError> {
+        let cipher = self.cipher.clone();
+        let account = account.clone();
+        Ok(self
+            .db
+            .with_conn(move |conn| accounts::save_account(conn, &account, &cipher))
+            .await?)
+    }
+
+    async fn delete_account(&self, id: i64) -> Result<bool, StoreError> {
+        Ok(self.db.with_conn(move |conn| accounts::delete_account(conn, id)).await?)
+    }
+
+    async fn set_current_account(&self, id: Option<i64>) -> Result<(), StoreError> {
+        Ok(self.db.with_conn(move |conn| accounts::set_current_account(conn, id)).await?)
+    }
+
+    async fn toggle_account_active(&self, id: i64) -> Result<bool, StoreError> {
+        Ok(self.db.with_conn(move |conn| accounts::toggle_account_active(conn, id)).await?)
+    }
+
+    async fn set_account_active(&self, id: i64, is_active: bool) -> Result<(), StoreError> {
+        Ok(self
+            .db
+            .with_conn(move |conn| accounts::set_account_active(conn, id, is_active))
+            .await?)
+    }
+
+    async fn update_account_tokens(
+        &self,
+        id: i64,
+        access_token: &str,
+        expires_at: i64,
+    ) -> Result<(), StoreError> {
+        let cipher = self.cipher.clone();
+        let access_token = access_token.to_string();
+        Ok(self
+            .db
+            .with_conn(move |conn| accounts::update_tokens(conn, id, &access_token, expires_at, &cipher))
+            .await?)
+    }
+
+    async fn get_proxy_config(&self) -> Result<ProxyConfig, StoreError> {
+        Ok(self.db.with_conn(config::get_proxy_config).await?)
+    }
+
+    async fn save_proxy_config(&self, config: &ProxyConfig) -> Result<(), StoreError> {
+        let config = config.clone();
+        Ok(self
+            .db
+            .with_conn(move |conn| super::config::save_proxy_config(conn, &config))
+            .await?)
+    }
+
+    async fn get_app_config(&self, key: &str) -> Result<Option<String>, StoreError> {
+        let key = key.to_string();
+        Ok(self.db.with_conn(move |conn| config::get_app_config(conn, &key)).await?)
+    }
+
+    async fn save_app_config(&self, key: &str, value: &str) -> Result<(), StoreError> {
+        let key = key.to_string();
+        let value = value.to_string();
+        Ok(self.db.with_conn(move |conn| config::save_app_config(conn, &key, &value)).await?)
+    }
+
+    async fn delete_app_config(&self, key: &str) -> Result<bool, StoreError> {
+        let key = key.to_string();
+        Ok(self.db.with_conn(move |conn| config::delete_app_config(conn, &key)).await?)
+    }
+
+    async fn get_all_app_config(&self) -> Result<Vec<AppConfig>, StoreError> {
+        Ok(self.db.with_conn(config::get_all_app_config).await?)
+    }
+
+    async fn insert_log(&self, log: &MonitorLog) -> Result<i64, StoreError> {
+        let log = log.clone();
+        Ok(self.db.with_writer(move |conn| monitor::insert_log(conn, &log)).await?)
+    }
+
+    async fn get_logs(&self, limit: u32, offset: u32) -> Result<Vec<MonitorLog>, StoreError> {
+        Ok(self.db.with_conn(move |conn| monitor::get_logs(conn, limit, offset)).await?)
+    }
+
+    async fn get_log_count(&self) -> Result<u64, StoreError> {
+        Ok(self.db.with_conn(monitor::get_log_count).await?)
+    }
+
+    async fn clear_logs(&self) -> Result<u64, StoreError> {
+        Ok(self.db.with_conn(monitor::clear_logs).await?)
+    }
+
+    async fn get_stats(&self) -> Result<LogStats, StoreError> {
+        Ok(self.db.with_conn(monitor::get_stats).await?)
+    }
+
+    async fn get_timeseries(
+        &self,
+        from: i64,
+        to: i64,
+        bucket_seconds: i64,
+        account_email: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Vec<TimeseriesBucket>, StoreError> {
+        let account_email = account_email.map(|s| s.to_string());
+        let model = model.map(|s| s.to_string());
+        Ok(self
+            .db
+            .with_conn(move |conn| {
+                monitor::get_timeseries(conn, from, to, bucket_seconds, account_email.as_deref(), model.as_deref())
+            })
+            .await?)
+    }
+
+    async fn list_api_keys(&self) -> Result<Vec<ApiKey>, StoreError> {
+        Ok(self.db.with_conn(api_keys::get_all_api_keys).await?)
+    }
+
+    async fn create_api_key(
+        &self,
+        key_hash: &str,
+        label: &str,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
+    ) -> Result<i64, StoreError> {
+        let key_hash = key_hash.to_string();
+        let label = label.to_string();
+        Ok(self
+            .db
+            .with_conn(move |conn| api_keys::create_api_key(conn, &key_hash, &label, valid_from, valid_until))
+            .await?)
+    }
+
+    async fn revoke_api_key(&self, id: i64) -> Result<bool, StoreError> {
+        Ok(self.db.with_conn(move |conn| api_keys::revoke_api_key(conn, id)).await?)
+    }
+}
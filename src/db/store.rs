@@ -0,0 +1,99 @@
+//! Pluggable storage backend
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use super::models::{Account, ApiKey, AppConfig, MonitorLog, ProxyConfig};
+use super::monitor::{LogStats, TimeseriesBucket};
+
+/// Error returned by a [`Store`] implementation
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("not found")]
+    NotFound,
+
+    #[error("backend error: {0}")]
+    Backend(String),
+}
+
+impl StoreError {
+    /// Map a store error onto the HTTP status code API handlers should return
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            StoreError::NotFound => StatusCode::NOT_FOUND,
+            StoreError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> Self {
+        StoreError::Backend(err.to_string())
+    }
+}
+
+impl IntoResponse for StoreError {
+    fn into_response(self) -> Response {
+        self.status_code().into_response()
+    }
+}
+
+/// Async storage backend used by the dashboard API.
+///
+/// Implementations are expected to be cheap to clone/share (typically wrapping
+/// a connection pool) since `AppState` holds one behind an `Arc<dyn Store>`.
+#[async_trait]
+pub trait Store: Send + Sync {
+    // -- accounts --------------------------------------------------------
+    async fn get_all_accounts(&self) -> Result<Vec<Account>, StoreError>;
+    async fn get_account_by_id(&self, id: i64) -> Result<Option<Account>, StoreError>;
+    async fn get_active_accounts(&self) -> Result<Vec<Account>, StoreError>;
+    async fn get_current_account(&self) -> Result<Option<Account>, StoreError>;
+    async fn save_account(&self, account: &Account) -> Result<i64, StoreError>;
+    async fn delete_account(&self, id: i64) -> Result<bool, StoreError>;
+    async fn set_current_account(&self, id: Option<i64>) -> Result<(), StoreError>;
+    async fn toggle_account_active(&self, id: i64) -> Result<bool, StoreError>;
+    async fn set_account_active(&self, id: i64, is_active: bool) -> Result<(), StoreError>;
+    async fn update_account_tokens(
+        &self,
+        id: i64,
+        access_token: &str,
+        expires_at: i64,
+    ) -> Result<(), StoreError>;
+
+    // -- config -----------------------------------------------------------
+    async fn get_proxy_config(&self) -> Result<ProxyConfig, StoreError>;
+    async fn save_proxy_config(&self, config: &ProxyConfig) -> Result<(), StoreError>;
+    async fn get_app_config(&self, key: &str) -> Result<Option<String>, StoreError>;
+    async fn save_app_config(&self, key: &str, value: &str) -> Result<(), StoreError>;
+    async fn delete_app_config(&self, key: &str) -> Result<bool, StoreError>;
+    async fn get_all_app_config(&self) -> Result<Vec<AppConfig>, StoreError>;
+
+    // -- monitor ------------------------------------------------------------
+    async fn insert_log(&self, log: &MonitorLog) -> Result<i64, StoreError>;
+    async fn get_logs(&self, limit: u32, offset: u32) -> Result<Vec<MonitorLog>, StoreError>;
+    async fn get_log_count(&self) -> Result<u64, StoreError>;
+    async fn clear_logs(&self) -> Result<u64, StoreError>;
+    async fn get_stats(&self) -> Result<LogStats, StoreError>;
+    #[allow(clippy::too_many_arguments)]
+    async fn get_timeseries(
+        &self,
+        from: i64,
+        to: i64,
+        bucket_seconds: i64,
+        account_email: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Vec<TimeseriesBucket>, StoreError>;
+
+    // -- api keys -----------------------------------------------------------
+    async fn list_api_keys(&self) -> Result<Vec<ApiKey>, StoreError>;
+    async fn create_api_key(
+        &self,
+        key_hash: &str,
+        label: &str,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
+    ) -> Result<i64, StoreError>;
+    async fn revoke_api_key(&self, id: i64) -> Result<bool, StoreError>;
+}
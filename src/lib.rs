@@ -3,6 +3,9 @@
 pub mod api;
 pub mod auth;
 pub mod config;
+pub mod crypto;
 pub mod db;
+pub mod metrics;
 pub mod proxy;
+pub mod ratelimit;
 pub mod utils;
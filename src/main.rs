@@ -1,11 +1,20 @@
-use axum::{routing::get, Router, Json};
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router, Json};
 use clap::Parser;
 use serde::Serialize;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tracing::info;
 use tracing_subscriber::{fmt, EnvFilter};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
-use better_manager::api::api_router;
+use better_manager::api::{api_router, ApiDoc, AppState};
+use better_manager::auth::{GoogleOAuth, TokenRefresher};
+use better_manager::config;
+use better_manager::crypto::TokenCipher;
+use better_manager::db::{self, get_default_db_path, Database, MetricsStore, PostgresStore, SqliteStore, Store};
+use better_manager::metrics::Registry;
+use better_manager::ratelimit::{LocalLimiter, RateLimiter, RedisLimiter};
 
 const DEFAULT_PORT: u16 = 8094;
 const DEFAULT_HOST: &str = "127.0.0.1";
@@ -33,6 +42,36 @@ struct Args {
     /// Log level (trace, debug, info, warn, error)
     #[arg(long, default_value = "info")]
     log_level: String,
+
+    /// Postgres connection URL to use instead of the local SQLite database
+    /// (falls back to the `DATABASE_URL` environment variable)
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: Option<String>,
+
+    /// Redis URL for distributed rate limiting across multiple instances
+    /// (falls back to an in-memory limiter when unset)
+    #[arg(long, env = "REDIS_URL")]
+    redis_url: Option<String>,
+
+    /// Number of pooled SQLite connections (ignored when `--database-url` is set)
+    #[arg(long, env = "DB_POOL_SIZE", default_value_t = db::DEFAULT_POOL_SIZE)]
+    db_pool_size: usize,
+
+    /// How often, in seconds, to scan for accounts needing a token refresh
+    #[arg(long, env = "TOKEN_REFRESH_INTERVAL_SECS", default_value_t = config::DEFAULT_TOKEN_REFRESH_INTERVAL_SECS)]
+    token_refresh_interval_secs: u64,
+
+    /// How long, in seconds, before expiry an account's token is refreshed
+    #[arg(long, env = "TOKEN_REFRESH_SKEW_SECS", default_value_t = config::DEFAULT_TOKEN_REFRESH_SKEW_SECS)]
+    token_refresh_skew_secs: i64,
+
+    /// Google OAuth client ID, used to refresh stored account tokens
+    #[arg(long, env = "GOOGLE_CLIENT_ID", default_value = "")]
+    google_client_id: String,
+
+    /// Google OAuth client secret, used to refresh stored account tokens
+    #[arg(long, env = "GOOGLE_CLIENT_SECRET", default_value = "")]
+    google_client_secret: String,
 }
 
 #[derive(Serialize)]
@@ -48,6 +87,74 @@ async fn health_check() -> Json<HealthResponse> {
     })
 }
 
+/// Prometheus-format scrape endpoint. Request counters, token sums, and the
+/// latency histogram come from the in-process registry; account gauges are
+/// cheap enough to read straight from the store on each scrape.
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let active = state
+        .db
+        .get_active_accounts()
+        .await
+        .map(|a| a.len() as u64)
+        .unwrap_or(0);
+    let total = state
+        .db
+        .get_all_accounts()
+        .await
+        .map(|a| a.len() as u64)
+        .unwrap_or(0);
+    let current = state.db.get_current_account().await.ok().flatten();
+
+    let body = state
+        .metrics
+        .render(active, total, current.as_ref().map(|a| a.email.as_str()));
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Build the configured rate limiter
+fn build_limiter(args: &Args) -> Arc<dyn RateLimiter> {
+    match &args.redis_url {
+        Some(url) => {
+            info!("Using Redis-backed rate limiter");
+            Arc::new(RedisLimiter::new(url).expect("Failed to create Redis rate limiter"))
+        }
+        None => {
+            info!("Using in-memory rate limiter");
+            Arc::new(LocalLimiter::new())
+        }
+    }
+}
+
+/// Build the configured storage backend
+async fn build_store(args: &Args) -> Arc<dyn Store> {
+    let data_dir = get_default_db_path()
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let cipher = Arc::new(TokenCipher::load(&data_dir).expect("Failed to load token encryption key"));
+
+    match &args.database_url {
+        Some(url) => {
+            info!("Using Postgres store");
+            let store = PostgresStore::connect(url, cipher)
+                .await
+                .expect("Failed to connect to Postgres");
+            Arc::new(store)
+        }
+        None => {
+            info!("Using SQLite store");
+            let database = Database::open_with_pool_size(None, args.db_pool_size)
+                .expect("Failed to open database");
+            database
+                .with_conn(db::run_migrations)
+                .await
+                .expect("Failed to run migrations");
+            Arc::new(SqliteStore::new(database, cipher))
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     // Parse CLI arguments
@@ -63,10 +170,29 @@ async fn main() {
         .compact()
         .init();
 
+    let metrics_registry = Arc::new(Registry::new());
+    let store: Arc<dyn Store> = Arc::new(MetricsStore::new(build_store(&args).await, metrics_registry.clone()));
+    let limiter = build_limiter(&args);
+
+    let oauth = GoogleOAuth::new(args.google_client_id.clone(), args.google_client_secret.clone());
+    let refresher = Arc::new(
+        TokenRefresher::new(store.clone(), oauth)
+            .with_skew_secs(args.token_refresh_skew_secs)
+            .with_scan_interval(std::time::Duration::from_secs(args.token_refresh_interval_secs)),
+    );
+    refresher.clone().spawn();
+
+    let state = AppState::new(store, limiter, refresher, metrics_registry);
+
     // Build the router
     let app = Router::new()
         .route("/health", get(health_check))
-        .nest("/api", api_router());
+        .route("/metrics", get(metrics))
+        .nest("/api", api_router())
+        // Served unauthenticated, unlike the rest of `/api`: Swagger UI has
+        // to be able to fetch the spec before a caller has a key to attach.
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
+        .with_state(state);
 
     // Bind to address
     let addr: SocketAddr = format!("{}:{}", args.host, args.port)
@@ -76,6 +202,8 @@ async fn main() {
     info!("🚀 Better Manager v{}", env!("CARGO_PKG_VERSION"));
     info!("   Server: http://{}", addr);
     info!("   Health: http://{}/health", addr);
+    info!("   Docs:   http://{}/docs", addr);
+    info!("   Metrics: http://{}/metrics", addr);
 
     if args.open {
         info!("   Opening browser...");
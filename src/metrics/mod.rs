@@ -0,0 +1,172 @@
+//! In-process Prometheus metrics registry for the proxy request log
+//!
+//! Counters and the latency histogram are updated synchronously whenever a
+//! [`MonitorLog`] row is written (see [`crate::db::MetricsStore`]), so a
+//! `/metrics` scrape never re-scans SQLite.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::db::MonitorLog;
+
+const LATENCY_BUCKETS_MS: &[f64] = &[10.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0];
+
+/// Escape a string for use as a Prometheus label value per the text
+/// exposition format: backslash, double quote, and newline must be escaped
+/// or a value containing one (e.g. a caller-supplied `model` name) corrupts
+/// the rest of the scrape.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[derive(Default)]
+struct Series {
+    success_count: u64,
+    error_count: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    latency_buckets: Vec<u64>,
+    latency_sum_ms: u64,
+    latency_count: u64,
+}
+
+impl Series {
+    fn new() -> Self {
+        Self {
+            latency_buckets: vec![0; LATENCY_BUCKETS_MS.len() + 1],
+            ..Default::default()
+        }
+    }
+}
+
+/// In-process Prometheus metrics registry, keyed by `(account_email, model)`
+#[derive(Default)]
+pub struct Registry {
+    series: Mutex<HashMap<(String, String), Series>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed request. Called once per [`MonitorLog`] insert.
+    pub fn record(&self, log: &MonitorLog) {
+        let key = (
+            log.account_email.clone().unwrap_or_default(),
+            log.model.clone().unwrap_or_default(),
+        );
+
+        let mut series = self.series.lock().unwrap();
+        let entry = series.entry(key).or_insert_with(Series::new);
+
+        if log.status_code < 400 {
+            entry.success_count += 1;
+        } else {
+            entry.error_count += 1;
+        }
+
+        entry.input_tokens += log.input_tokens.unwrap_or(0).max(0) as u64;
+        entry.output_tokens += log.output_tokens.unwrap_or(0).max(0) as u64;
+
+        let latency = log.latency_ms as f64;
+        entry.latency_sum_ms += log.latency_ms as u64;
+        entry.latency_count += 1;
+        // Counted once in the first bucket that holds it (the last slot is
+        // +Inf); render() makes the counts cumulative.
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        entry.latency_buckets[bucket] += 1;
+    }
+
+    /// Render recorded series plus the given account gauges as Prometheus
+    /// text exposition format
+    pub fn render(&self, active_accounts: u64, total_accounts: u64, current_account: Option<&str>) -> String {
+        let series = self.series.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP better_manager_requests_total Total proxy requests by outcome\n");
+        out.push_str("# TYPE better_manager_requests_total counter\n");
+        for ((email, model), s) in series.iter() {
+            let email = escape_label_value(email);
+            let model = escape_label_value(model);
+            out.push_str(&format!(
+                "better_manager_requests_total{{account_email=\"{email}\",model=\"{model}\",status=\"success\"}} {}\n",
+                s.success_count
+            ));
+            out.push_str(&format!(
+                "better_manager_requests_total{{account_email=\"{email}\",model=\"{model}\",status=\"error\"}} {}\n",
+                s.error_count
+            ));
+        }
+
+        out.push_str("# HELP better_manager_input_tokens_total Total input tokens consumed\n");
+        out.push_str("# TYPE better_manager_input_tokens_total counter\n");
+        for ((email, model), s) in series.iter() {
+            let email = escape_label_value(email);
+            let model = escape_label_value(model);
+            out.push_str(&format!(
+                "better_manager_input_tokens_total{{account_email=\"{email}\",model=\"{model}\"}} {}\n",
+                s.input_tokens
+            ));
+        }
+
+        out.push_str("# HELP better_manager_output_tokens_total Total output tokens produced\n");
+        out.push_str("# TYPE better_manager_output_tokens_total counter\n");
+        for ((email, model), s) in series.iter() {
+            let email = escape_label_value(email);
+            let model = escape_label_value(model);
+            out.push_str(&format!(
+                "better_manager_output_tokens_total{{account_email=\"{email}\",model=\"{model}\"}} {}\n",
+                s.output_tokens
+            ));
+        }
+
+        out.push_str("# HELP better_manager_request_latency_ms Request latency in milliseconds\n");
+        out.push_str("# TYPE better_manager_request_latency_ms histogram\n");
+        for ((email, model), s) in series.iter() {
+            let email = escape_label_value(email);
+            let model = escape_label_value(model);
+            let mut cumulative = 0u64;
+            for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+                cumulative += s.latency_buckets[i];
+                out.push_str(&format!(
+                    "better_manager_request_latency_ms_bucket{{account_email=\"{email}\",model=\"{model}\",le=\"{bound}\"}} {cumulative}\n"
+                ));
+            }
+            cumulative += s.latency_buckets[LATENCY_BUCKETS_MS.len()];
+            out.push_str(&format!(
+                "better_manager_request_latency_ms_bucket{{account_email=\"{email}\",model=\"{model}\",le=\"+Inf\"}} {cumulative}\n"
+            ));
+            out.push_str(&format!(
+                "better_manager_request_latency_ms_sum{{account_email=\"{email}\",model=\"{model}\"}} {}\n",
+                s.latency_sum_ms
+            ));
+            out.push_str(&format!(
+                "better_manager_request_latency_ms_count{{account_email=\"{email}\",model=\"{model}\"}} {}\n",
+                s.latency_count
+            ));
+        }
+
+        out.push_str("# HELP better_manager_active_accounts Number of accounts currently active\n");
+        out.push_str("# TYPE better_manager_active_accounts gauge\n");
+        out.push_str(&format!("better_manager_active_accounts {active_accounts}\n"));
+
+        out.push_str("# HELP better_manager_accounts_total Total configured accounts\n");
+        out.push_str("# TYPE better_manager_accounts_total gauge\n");
+        out.push_str(&format!("better_manager_accounts_total {total_accounts}\n"));
+
+        out.push_str("# HELP better_manager_current_account Account currently selected by the dashboard\n");
+        out.push_str("# TYPE better_manager_current_account gauge\n");
+        if let Some(email) = current_account {
+            let email = escape_label_value(email);
+            out.push_str(&format!(
+                "better_manager_current_account{{account_email=\"{email}\"}} 1\n"
+            ));
+        }
+
+        out
+    }
+}
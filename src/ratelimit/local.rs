@@ -0,0 +1,54 @@
+//! In-memory token-bucket limiter
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{apply_token_bucket, Decision, RateLimiter};
+
+struct Bucket {
+    tokens: f64,
+    last_refill_secs: f64,
+}
+
+/// Single-process token-bucket limiter keyed by an arbitrary string
+/// (an API key or account email)
+#[derive(Default)]
+pub struct LocalLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl LocalLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+#[async_trait]
+impl RateLimiter for LocalLimiter {
+    async fn check(&self, key: &str, max_per_period: u32, period: Duration) -> Decision {
+        let now = now_secs();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let bucket = buckets.entry(key.to_string()).or_insert_with(|| Bucket {
+            tokens: max_per_period as f64,
+            last_refill_secs: now,
+        });
+
+        let (tokens, decision) =
+            apply_token_bucket(bucket.tokens, bucket.last_refill_secs, now, max_per_period, period);
+
+        bucket.tokens = tokens;
+        bucket.last_refill_secs = now;
+
+        decision
+    }
+}
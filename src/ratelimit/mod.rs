@@ -0,0 +1,75 @@
+//! Rate limiting subsystem
+//!
+//! Callers are throttled with a token-bucket: each key accrues
+//! `max_per_period` tokens every `period`, one token is spent per request,
+//! and requests are rejected once the bucket is empty. [`local::LocalLimiter`]
+//! keeps buckets in-process; [`redis::RedisLimiter`] shares them across
+//! instances via Redis while caching recent decisions locally so most
+//! requests never round-trip to Redis.
+
+pub mod local;
+pub mod redis;
+
+use async_trait::async_trait;
+use std::time::Duration;
+
+pub use local::LocalLimiter;
+pub use redis::RedisLimiter;
+
+/// Result of a rate limit check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    /// Seconds the caller should wait before retrying when `allowed` is false
+    pub retry_after_secs: u64,
+}
+
+impl Decision {
+    pub fn allow() -> Self {
+        Self {
+            allowed: true,
+            retry_after_secs: 0,
+        }
+    }
+
+    pub fn deny(retry_after_secs: u64) -> Self {
+        Self {
+            allowed: false,
+            retry_after_secs,
+        }
+    }
+}
+
+/// A keyed token-bucket rate limiter
+#[async_trait]
+pub trait RateLimiter: Send + Sync {
+    /// Check and consume a token for `key`, allowing `max_per_period`
+    /// requests per `period`
+    async fn check(&self, key: &str, max_per_period: u32, period: Duration) -> Decision;
+}
+
+/// Compute the token-bucket decision given the bucket's prior state.
+///
+/// Used by [`local::LocalLimiter`]; [`redis::RedisLimiter`] runs the same
+/// refill arithmetic inside its Lua script.
+pub(crate) fn apply_token_bucket(
+    tokens: f64,
+    last_refill_secs: f64,
+    now_secs: f64,
+    max_per_period: u32,
+    period: Duration,
+) -> (f64, Decision) {
+    let max = max_per_period as f64;
+    let period_secs = period.as_secs_f64().max(0.001);
+
+    let elapsed = (now_secs - last_refill_secs).max(0.0);
+    let refilled = (tokens + elapsed / period_secs * max).min(max);
+
+    if refilled >= 1.0 {
+        (refilled - 1.0, Decision::allow())
+    } else {
+        let missing = 1.0 - refilled;
+        let retry_after = (missing * period_secs / max).ceil() as u64;
+        (refilled, Decision::deny(retry_after.max(1)))
+    }
+}
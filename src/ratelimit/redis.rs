@@ -0,0 +1,152 @@
+//! Redis-backed token-bucket limiter for multi-instance deployments
+//!
+//! The bucket arithmetic runs inside a Lua script so the read-refill-write
+//! cycle is atomic even with many proxy instances sharing one Redis. To avoid
+//! a round trip on every request, each instance keeps a short-lived local
+//! estimate of the remaining tokens ("deferred" mode) and only re-syncs with
+//! Redis once that estimate says the caller is close to being limited.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use super::{Decision, RateLimiter};
+
+/// Atomically refills and spends one token for `KEYS[1]`.
+/// `ARGV`: max_per_period, period_secs, now_secs.
+/// Returns `{allowed (0|1), tokens_remaining}`.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local key = KEYS[1]
+local max = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+
+local tokens = max
+local last = now
+local data = redis.call('GET', key)
+if data then
+    local sep = string.find(data, ':')
+    tokens = tonumber(string.sub(data, 1, sep - 1))
+    last = tonumber(string.sub(data, sep + 1))
+end
+
+local elapsed = now - last
+if elapsed < 0 then elapsed = 0 end
+tokens = math.min(max, tokens + (elapsed / period) * max)
+
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+
+redis.call('SET', key, tostring(tokens) .. ':' .. tostring(now), 'EX', math.max(1, math.ceil(period * 2)))
+return {allowed, tostring(tokens)}
+"#;
+
+struct LocalEstimate {
+    tokens: f64,
+    synced_at: Instant,
+}
+
+/// Distributed token-bucket limiter backed by Redis, with a short-lived
+/// local cache so only a fraction of requests pay the Redis round trip
+pub struct RedisLimiter {
+    client: redis::Client,
+    script: redis::Script,
+    cache: Mutex<HashMap<String, LocalEstimate>>,
+    /// How long a local estimate can be trusted before re-syncing with Redis
+    cache_ttl: Duration,
+    /// Re-sync early once the local estimate drops below this many tokens,
+    /// so we don't under- or over-admit requests near the limit
+    resync_threshold: f64,
+}
+
+impl RedisLimiter {
+    pub fn new(redis_url: &str) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            script: redis::Script::new(TOKEN_BUCKET_SCRIPT),
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Duration::from_millis(500),
+            resync_threshold: 2.0,
+        })
+    }
+
+    async fn sync_with_redis(&self, key: &str, max_per_period: u32, period: Duration) -> Decision {
+        let now = now_secs();
+
+        let conn = match self.client.get_multiplexed_async_connection().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!("Redis rate limiter unavailable, failing open: {err}");
+                return Decision::allow();
+            }
+        };
+        let mut conn = conn;
+
+        let result: redis::RedisResult<(i64, String)> = self
+            .script
+            .key(key)
+            .arg(max_per_period)
+            .arg(period.as_secs_f64())
+            .arg(now)
+            .invoke_async(&mut conn)
+            .await;
+
+        match result {
+            Ok((allowed, tokens_str)) => {
+                let tokens: f64 = tokens_str.parse().unwrap_or(0.0);
+                self.cache.lock().unwrap().insert(
+                    key.to_string(),
+                    LocalEstimate {
+                        tokens,
+                        synced_at: Instant::now(),
+                    },
+                );
+
+                if allowed == 1 {
+                    Decision::allow()
+                } else {
+                    let period_secs = period.as_secs_f64().max(0.001);
+                    let missing = 1.0 - tokens;
+                    let retry_after =
+                        (missing * period_secs / max_per_period as f64).ceil() as u64;
+                    Decision::deny(retry_after.max(1))
+                }
+            }
+            Err(err) => {
+                warn!("Redis rate limiter script failed, failing open: {err}");
+                Decision::allow()
+            }
+        }
+    }
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+#[async_trait]
+impl RateLimiter for RedisLimiter {
+    async fn check(&self, key: &str, max_per_period: u32, period: Duration) -> Decision {
+        {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(estimate) = cache.get_mut(key) {
+                if estimate.synced_at.elapsed() < self.cache_ttl
+                    && estimate.tokens >= self.resync_threshold
+                {
+                    estimate.tokens -= 1.0;
+                    return Decision::allow();
+                }
+            }
+        }
+
+        self.sync_with_redis(key, max_per_period, period).await
+    }
+}